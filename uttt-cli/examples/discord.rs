@@ -0,0 +1,185 @@
+//! Discord bot that plays against the users of a channel. Every channel has its own game, and
+//! the users of the channel play X together.
+//!
+//! Run with `DISCORD_TOKEN=<token> cargo run --release --features discord --example discord` from
+//! the `uttt-cli` directory. The bot needs the message content intent.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use serenity::all::ChannelId;
+use serenity::async_trait;
+use serenity::model::channel::Message;
+use serenity::prelude::*;
+use uttt_core::{Board, Move, Outcome, Rules};
+use uttt_engine::{explain_move, MctsEngine};
+
+/// Prefix of the messages the bot responds to.
+const COMMAND_PREFIX: &str = "!uttt";
+/// Think time of the engine in milliseconds.
+const THINK_MS: u128 = 1000;
+
+const HELP: &str = "Commands:
+`!uttt new`: start a new game in this channel, you play X
+`!uttt <major>,<minor>`: play a move, e.g. `!uttt 4,0`
+`!uttt show`: show the board
+`!uttt resign`: give up the game";
+const NO_GAME: &str = "There is no game in this channel. Start one with `!uttt new`.";
+
+struct Game {
+    board: Board,
+    /// Whether the engine is searching its reply. Moves are rejected in the meantime.
+    thinking: bool,
+}
+
+#[derive(Default)]
+struct Handler {
+    games: Mutex<HashMap<ChannelId, Game>>,
+}
+
+impl Handler {
+    fn games(&self) -> MutexGuard<'_, HashMap<ChannelId, Game>> {
+        self.games.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Handles a command and returns the reply.
+    async fn handle(&self, channel: ChannelId, command: &str) -> String {
+        match command {
+            "" | "help" => HELP.to_string(),
+            "new" => {
+                let board = Board::with_rules(Rules {
+                    strict_game_over: true,
+                });
+                self.games().insert(
+                    channel,
+                    Game {
+                        board,
+                        thinking: false,
+                    },
+                );
+                format!("New game, you play X.\n{}", render(&board))
+            }
+            "show" => match self.games().get(&channel) {
+                Some(game) => render(&game.board),
+                None => NO_GAME.to_string(),
+            },
+            "resign" => match self.games().remove(&channel) {
+                Some(_) => "You resigned. Start a new game with `!uttt new`.".to_string(),
+                None => NO_GAME.to_string(),
+            },
+            m => match m.parse() {
+                Ok(m) => self.play(channel, m).await,
+                Err(err) => format!("{}. {}", err, HELP),
+            },
+        }
+    }
+
+    /// Plays the move of the users and the reply of the engine.
+    async fn play(&self, channel: ChannelId, m: Move) -> String {
+        let board = {
+            let mut games = self.games();
+            let Some(game) = games.get_mut(&channel) else {
+                return NO_GAME.to_string();
+            };
+            if game.thinking {
+                return "Wait for my move.".to_string();
+            }
+            match game.board.advance_state_with_report(m) {
+                Ok(board) => {
+                    game.board = board;
+                    game.thinking = true;
+                    board
+                }
+                Err(rejection) => return format!("Illegal move: {}.", rejection.error),
+            }
+        };
+        if let Some(result) = game_over(&board) {
+            self.games().remove(&channel);
+            return format!("{}\n{}", render(&board), result);
+        }
+
+        let reply = tokio::task::spawn_blocking(move || {
+            let mut mcts = MctsEngine::for_position(board);
+            mcts.run_search(THINK_MS);
+            mcts.best_move().expect("game is in progress")
+        })
+        .await
+        .expect("search panicked");
+        let reasons: Vec<_> = explain_move(&board, reply)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let searched = board;
+        let board = board
+            .advance_state(reply)
+            .expect("engine plays legal moves");
+
+        let mut games = self.games();
+        // The game might have been resigned or restarted in the meantime.
+        match games.get_mut(&channel) {
+            Some(game) if game.thinking && game.board == searched => {
+                game.board = board;
+                game.thinking = false;
+            }
+            _ => return String::new(),
+        }
+        let mut text = format!("I play {},{}", reply.major, reply.minor);
+        if !reasons.is_empty() {
+            text += &format!(" ({})", reasons.join(", "));
+        }
+        text += &format!(".\n{}", render(&board));
+        if let Some(result) = game_over(&board) {
+            games.remove(&channel);
+            text += &format!("\n{}", result);
+        }
+        text
+    }
+}
+
+/// Renders the board as a code block, so that the cells line up.
+fn render(board: &Board) -> String {
+    format!("```\n{}\n```", board)
+}
+
+/// Returns the result of the game, if it is over.
+fn game_over(board: &Board) -> Option<&'static str> {
+    match board.winner()? {
+        Outcome::X => Some("You won!"),
+        Outcome::O => Some("I won!"),
+        Outcome::Tie => Some("It's a tie!"),
+    }
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn message(&self, ctx: Context, msg: Message) {
+        if msg.author.bot {
+            return;
+        }
+        let Some(command) = msg.content.strip_prefix(COMMAND_PREFIX) else {
+            return;
+        };
+        let reply = self.handle(msg.channel_id, command.trim()).await;
+        if reply.is_empty() {
+            return;
+        }
+        if let Err(err) = msg.channel_id.say(&ctx.http, reply).await {
+            eprintln!("Failed to send message: {}", err);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let token = std::env::var("DISCORD_TOKEN").expect("DISCORD_TOKEN is not set");
+    let intents = GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::DIRECT_MESSAGES
+        | GatewayIntents::MESSAGE_CONTENT;
+    let mut client = Client::builder(&token, intents)
+        .event_handler(Handler::default())
+        .await
+        .expect("failed to create client");
+    if let Err(err) = client.start().await {
+        eprintln!("Client error: {}", err);
+    }
+}
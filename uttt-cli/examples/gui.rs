@@ -0,0 +1,296 @@
+//! Native desktop frontend built with egui. You play X against the engine.
+//!
+//! Run with `cargo run --release --features gui --example gui` from the `uttt-cli` directory.
+
+use std::sync::mpsc::Receiver;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use eframe::egui::{self, Align2, Color32, FontId, Pos2, Rect, Sense, Stroke, StrokeKind, Vec2};
+use uttt_core::{Board, Move, Outcome, Player, Rules};
+use uttt_engine::{explain_move, MctsEngine, SearchInfo};
+
+/// Difficulties the engine can play at, as think times in milliseconds.
+const DIFFICULTIES: [(&str, u128); 6] = [
+    ("Noob", 50),
+    ("Easy", 100),
+    ("Medium", 500),
+    ("Hard", 1000),
+    ("Boss", 2000),
+    ("Insane", 5000),
+];
+/// Interval between two repaints while the engine is thinking, to show its progress.
+const SEARCH_REPAINT_INTERVAL: Duration = Duration::from_millis(50);
+
+const HIGHLIGHT: Color32 = Color32::from_rgb(60, 70, 40);
+const X_COLOR: Color32 = Color32::from_rgb(220, 90, 90);
+const O_COLOR: Color32 = Color32::from_rgb(90, 140, 220);
+
+/// A search running on another thread.
+struct Search {
+    handle: JoinHandle<MctsEngine>,
+    receiver: Receiver<SearchInfo>,
+    info: Option<SearchInfo>,
+}
+
+struct App {
+    board: Board,
+    history: Vec<(Player, Move, Board)>,
+    think_ms: u128,
+    search: Option<Search>,
+    /// Progress of the last finished search.
+    last_info: Option<SearchInfo>,
+    message: String,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            board: Board::with_rules(Rules {
+                strict_game_over: true,
+            }),
+            history: Vec::new(),
+            think_ms: 100,
+            search: None,
+            last_info: None,
+            message: String::new(),
+        }
+    }
+}
+
+impl App {
+    fn play(&mut self, m: Move) {
+        let player = self.board.player_to_move;
+        self.board = self.board.advance_state(m).expect("move is legal");
+        self.history.push((player, m, self.board));
+    }
+
+    fn start_search(&mut self) {
+        let (handle, receiver) =
+            MctsEngine::for_position(self.board).run_search_streaming(self.think_ms);
+        self.search = Some(Search {
+            handle,
+            receiver,
+            info: None,
+        });
+        self.message = "Thinking...".to_string();
+    }
+
+    /// Collects the progress of the running search and plays its move once it is done.
+    fn poll_search(&mut self) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        if let Some(info) = search.receiver.try_iter().last() {
+            search.info = Some(info);
+        }
+        if !search.handle.is_finished() {
+            return;
+        }
+
+        let search = self.search.take().expect("search is running");
+        let engine = search.handle.join().expect("search thread panicked");
+        self.last_info = search.receiver.try_iter().last().or(search.info);
+        let m = engine.best_move().expect("game is in progress");
+        let reasons: Vec<_> = explain_move(&self.board, m)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        self.message = if reasons.is_empty() {
+            format!("AI played {}.", m)
+        } else {
+            format!("AI played {}: {}.", m, reasons.join(", "))
+        };
+        self.play(m);
+    }
+
+    fn on_cell_clicked(&mut self, m: Move) {
+        if self.search.is_some() || self.board.player_to_move != Player::X {
+            return;
+        }
+        if let Err(err) = self.board.check_move(m) {
+            self.message = format!("Illegal move: {}.", err);
+            return;
+        }
+        self.play(m);
+        if self.board.count_moves() > 0 {
+            self.start_search();
+        }
+    }
+
+    fn board_ui(&mut self, ui: &mut egui::Ui) {
+        let size = ui.available_size().min_elem();
+        let (response, painter) = ui.allocate_painter(Vec2::splat(size), Sense::click());
+        let origin = response.rect.min;
+        let cell = size / 9.0;
+        let cell_rect = |row: u32, col: u32| {
+            Rect::from_min_size(
+                origin + Vec2::new(col as f32 * cell, row as f32 * cell),
+                Vec2::splat(cell),
+            )
+        };
+        let mark = |rect: Rect, player: Player, size: f32| {
+            let (text, color) = match player {
+                Player::X => ("X", X_COLOR),
+                Player::O => ("O", O_COLOR),
+            };
+            painter.text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                text,
+                FontId::proportional(size),
+                color,
+            );
+        };
+
+        if self.board.player_to_move == Player::X && self.search.is_none() {
+            for m in self.board.moves() {
+                let (row, col) = m.global();
+                painter.rect_filled(cell_rect(row, col), 0.0, HIGHLIGHT);
+            }
+        }
+        for (major, minor, player) in self.board.cells() {
+            if let Some(player) = player {
+                let (row, col) = Move::new(major, minor).global();
+                mark(cell_rect(row, col), player, cell * 0.7);
+            }
+        }
+        for major in 0..9 {
+            let sub_board_rect = Rect::from_min_size(
+                origin + Vec2::new((major % 3) as f32, (major / 3) as f32) * cell * 3.0,
+                Vec2::splat(cell * 3.0),
+            );
+            let winner = self.board.sub_board_winner(major).and_then(Outcome::player);
+            if let Some(player) = winner {
+                painter.rect_filled(sub_board_rect, 0.0, Color32::from_black_alpha(160));
+                mark(sub_board_rect, player, cell * 2.5);
+            }
+        }
+        for i in 0..=9 {
+            let width = if i % 3 == 0 { 3.0 } else { 1.0 };
+            let stroke = Stroke::new(width, Color32::GRAY);
+            let offset = i as f32 * cell;
+            painter.line_segment(
+                [
+                    origin + Vec2::new(offset, 0.0),
+                    origin + Vec2::new(offset, size),
+                ],
+                stroke,
+            );
+            painter.line_segment(
+                [
+                    origin + Vec2::new(0.0, offset),
+                    origin + Vec2::new(size, offset),
+                ],
+                stroke,
+            );
+        }
+        painter.rect_stroke(
+            response.rect,
+            0.0,
+            Stroke::new(3.0, Color32::GRAY),
+            StrokeKind::Inside,
+        );
+        if let Some([first, _, last]) = self.board.winning_line() {
+            let center = |major: u32| {
+                origin
+                    + (Vec2::new((major % 3) as f32, (major / 3) as f32) + Vec2::splat(0.5))
+                        * cell
+                        * 3.0
+            };
+            let color = match self.board.winner() {
+                Some(Outcome::X) => X_COLOR,
+                _ => O_COLOR,
+            };
+            painter.line_segment([center(first), center(last)], Stroke::new(6.0, color));
+        }
+
+        if let Some(Pos2 { x, y }) = response
+            .interact_pointer_pos()
+            .filter(|_| response.clicked())
+        {
+            let col = ((x - origin.x) / cell).clamp(0.0, 8.0) as u32;
+            let row = ((y - origin.y) / cell).clamp(0.0, 8.0) as u32;
+            self.on_cell_clicked(Move::from_global(row, col));
+        }
+    }
+
+    fn side_panel_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Ultimate TicTacToe");
+        ui.separator();
+
+        ui.label("Difficulty:");
+        ui.horizontal_wrapped(|ui| {
+            for (name, think_ms) in DIFFICULTIES {
+                ui.selectable_value(&mut self.think_ms, think_ms, name);
+            }
+        });
+        if ui.button("New game").clicked() && self.search.is_none() {
+            let think_ms = self.think_ms;
+            *self = Self {
+                think_ms,
+                ..Self::default()
+            };
+        }
+        ui.separator();
+
+        match self.board.winner() {
+            Some(Outcome::X) => ui.label("You won!"),
+            Some(Outcome::O) => ui.label("AI won!"),
+            Some(Outcome::Tie) => ui.label("Tie!"),
+            None => ui.label(&self.message),
+        };
+
+        ui.separator();
+        ui.label("Analysis:");
+        match self
+            .search
+            .as_ref()
+            .and_then(|search| search.info)
+            .or(self.last_info)
+        {
+            Some(info) => {
+                ui.label(format!("Iterations: {}", info.iterations));
+                ui.label(format!("Simulated moves: {}", info.moves));
+                if let Some(m) = info.best_move {
+                    ui.label(format!("Best move: {}", m));
+                }
+                if let Some(win_rate) = info.win_rate {
+                    ui.label(format!("AI win rate: {:.0}%", win_rate * 100.0));
+                }
+            }
+            None => {
+                ui.label("No search yet.");
+            }
+        }
+
+        ui.separator();
+        ui.label("Moves:");
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (i, (player, m, _)) in self.history.iter().enumerate() {
+                ui.label(format!("{}. {:?} {}", i + 1, player, m));
+            }
+        });
+    }
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_search();
+        egui::SidePanel::right("side_panel")
+            .min_width(220.0)
+            .show(ctx, |ui| self.side_panel_ui(ui));
+        egui::CentralPanel::default().show(ctx, |ui| self.board_ui(ui));
+        if self.search.is_some() {
+            ctx.request_repaint_after(SEARCH_REPAINT_INTERVAL);
+        }
+    }
+}
+
+fn main() -> eframe::Result {
+    eframe::run_native(
+        "Ultimate TicTacToe",
+        eframe::NativeOptions::default(),
+        Box::new(|_cc| Ok(Box::<App>::default())),
+    )
+}
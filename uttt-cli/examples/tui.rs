@@ -0,0 +1,320 @@
+//! Full-screen terminal client built with ratatui. You play X against the engine.
+//!
+//! Run with `cargo run --release --features tui --example tui` from the `uttt-cli` directory.
+//! Move the cursor with the arrow keys (or `hjkl`) and play with enter or space, or click on a
+//! cell. `+` and `-` change the difficulty, `s` makes the engine play its best move so far, `n`
+//! starts a new game and `q` quits.
+
+use std::io;
+use std::sync::mpsc::Receiver;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use ratatui::crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+    MouseEventKind,
+};
+use ratatui::crossterm::execute;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use uttt_core::{Board, Move, Outcome, Player, Rules};
+use uttt_engine::{explain_move, MctsEngine, SearchHandle, SearchInfo};
+
+/// Difficulties the engine can play at, as think times in milliseconds.
+const DIFFICULTIES: [(&str, u128); 6] = [
+    ("Noob", 50),
+    ("Easy", 100),
+    ("Medium", 500),
+    ("Hard", 1000),
+    ("Boss", 2000),
+    ("Insane", 5000),
+];
+/// How long to wait for input before redrawing, to show the progress of the engine.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Width of the board including its border: 9 cells of 3 columns and 2 separators.
+const BOARD_WIDTH: u16 = 9 * 3 + 2 + 2;
+/// Height of the board including its border: 9 cells and 2 separators.
+const BOARD_HEIGHT: u16 = 9 + 2 + 2;
+
+/// A search running on another thread.
+struct Search {
+    handle: JoinHandle<MctsEngine>,
+    stop: SearchHandle,
+    receiver: Receiver<SearchInfo>,
+    info: Option<SearchInfo>,
+}
+
+struct App {
+    board: Board,
+    history: Vec<(Player, Move, Board)>,
+    difficulty: usize,
+    /// Row and column of the cursor on the 9x9 grid.
+    cursor: (u32, u32),
+    search: Option<Search>,
+    /// Progress of the last finished search.
+    last_info: Option<SearchInfo>,
+    message: String,
+    /// Area of the board in the last drawn frame, for mouse input.
+    board_area: Rect,
+}
+
+impl App {
+    fn new(difficulty: usize) -> Self {
+        Self {
+            board: Board::with_rules(Rules {
+                strict_game_over: true,
+            }),
+            history: Vec::new(),
+            difficulty,
+            cursor: (4, 4),
+            search: None,
+            last_info: None,
+            message: "Your move.".to_string(),
+            board_area: Rect::default(),
+        }
+    }
+
+    fn play(&mut self, m: Move) {
+        let player = self.board.player_to_move;
+        self.board = self.board.advance_state(m).expect("move is legal");
+        self.history.push((player, m, self.board));
+    }
+
+    fn play_cursor(&mut self) {
+        let (row, col) = self.cursor;
+        let m = Move::from_global(row, col);
+        if self.search.is_some() || self.board.player_to_move != Player::X {
+            return;
+        }
+        if let Err(err) = self.board.check_move(m) {
+            self.message = format!("Illegal move: {}.", err);
+            return;
+        }
+        self.play(m);
+        if self.board.count_moves() > 0 {
+            let think_ms = DIFFICULTIES[self.difficulty].1;
+            let mcts = MctsEngine::for_position(self.board);
+            let stop = mcts.search_handle();
+            let (handle, receiver) = mcts.run_search_streaming(think_ms);
+            self.search = Some(Search {
+                handle,
+                stop,
+                receiver,
+                info: None,
+            });
+            self.message = "Thinking...".to_string();
+        }
+    }
+
+    /// Collects the progress of the running search and plays its move once it is done.
+    fn poll_search(&mut self) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        if let Some(info) = search.receiver.try_iter().last() {
+            search.info = Some(info);
+        }
+        if !search.handle.is_finished() {
+            return;
+        }
+
+        let search = self.search.take().expect("search is running");
+        let engine = search.handle.join().expect("search thread panicked");
+        self.last_info = search.receiver.try_iter().last().or(search.info);
+        let m = engine.best_move().expect("game is in progress");
+        let reasons: Vec<_> = explain_move(&self.board, m)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        self.message = if reasons.is_empty() {
+            format!("AI played {}.", m)
+        } else {
+            format!("AI played {}: {}.", m, reasons.join(", "))
+        };
+        self.play(m);
+    }
+
+    /// Handles an input event. Returns `false` if the app should quit.
+    fn handle_event(&mut self, event: Event) -> bool {
+        let (row, col) = &mut self.cursor;
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return false,
+                KeyCode::Up | KeyCode::Char('k') => *row = row.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => *row = (*row + 1).min(8),
+                KeyCode::Left | KeyCode::Char('h') => *col = col.saturating_sub(1),
+                KeyCode::Right | KeyCode::Char('l') => *col = (*col + 1).min(8),
+                KeyCode::Enter | KeyCode::Char(' ') => self.play_cursor(),
+                KeyCode::Char('+') => {
+                    self.difficulty = (self.difficulty + 1).min(DIFFICULTIES.len() - 1)
+                }
+                KeyCode::Char('-') => self.difficulty = self.difficulty.saturating_sub(1),
+                KeyCode::Char('s') => {
+                    if let Some(search) = &self.search {
+                        search.stop.stop();
+                    }
+                }
+                KeyCode::Char('n') if self.search.is_none() => *self = Self::new(self.difficulty),
+                _ => {}
+            },
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(cell) = self.cell_at(mouse.column, mouse.row) {
+                    self.cursor = cell;
+                    self.play_cursor();
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Returns the row and column of the cell at the given terminal position, if any.
+    fn cell_at(&self, x: u16, y: u16) -> Option<(u32, u32)> {
+        let x = x.checked_sub(self.board_area.x + 1)? as u32;
+        let y = y.checked_sub(self.board_area.y + 1)? as u32;
+        // Every sub-board is followed by a separator.
+        let (sub_col, x) = (x / 10, x % 10);
+        let (sub_row, y) = (y / 4, y % 4);
+        (sub_col < 3 && sub_row < 3 && x < 9 && y < 3)
+            .then_some((sub_row * 3 + y, sub_col * 3 + x / 3))
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [board_area, side_area] =
+            Layout::horizontal([Constraint::Length(BOARD_WIDTH), Constraint::Min(30)])
+                .areas(frame.area());
+        let [board_area, _] =
+            Layout::vertical([Constraint::Length(BOARD_HEIGHT), Constraint::Min(0)])
+                .areas(board_area);
+        self.board_area = board_area;
+        frame.render_widget(
+            Paragraph::new(self.board_lines()).block(Block::bordered().title(" Board ")),
+            board_area,
+        );
+
+        let [status_area, eval_area, moves_area] = Layout::vertical([
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Min(3),
+        ])
+        .areas(side_area);
+
+        let status = match self.board.winner() {
+            Some(Outcome::X) => "You won! Press n for a new game.".to_string(),
+            Some(Outcome::O) => "AI won! Press n for a new game.".to_string(),
+            Some(Outcome::Tie) => "Tie! Press n for a new game.".to_string(),
+            None => self.message.clone(),
+        };
+        let status = vec![
+            Line::from(format!(
+                "Difficulty: {} (+/-)",
+                DIFFICULTIES[self.difficulty].0
+            )),
+            Line::from(status),
+        ];
+        frame.render_widget(
+            Paragraph::new(status)
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title(" Status ")),
+            status_area,
+        );
+
+        let info = self
+            .search
+            .as_ref()
+            .and_then(|search| search.info)
+            .or(self.last_info);
+        let eval = match info {
+            Some(info) => vec![
+                Line::from(format!("Iterations: {}", info.iterations)),
+                Line::from(format!("Simulated moves: {}", info.moves)),
+                Line::from(format!(
+                    "Best move: {}",
+                    info.best_move.map_or("-".to_string(), |m| m.to_string())
+                )),
+                Line::from(format!(
+                    "AI win rate: {}",
+                    info.win_rate
+                        .map_or("-".to_string(), |rate| format!("{:.0}%", rate * 100.0))
+                )),
+            ],
+            None => vec![Line::from("No search yet.")],
+        };
+        frame.render_widget(
+            Paragraph::new(eval).block(Block::bordered().title(" Evaluation ")),
+            eval_area,
+        );
+
+        // Show the latest moves if the list does not fit.
+        let visible = moves_area.height.saturating_sub(2) as usize;
+        let moves = self
+            .history
+            .iter()
+            .enumerate()
+            .skip(self.history.len().saturating_sub(visible))
+            .map(|(i, (player, m, _))| format!("{}. {:?} {}", i + 1, player, m));
+        frame.render_widget(
+            List::new(moves).block(Block::bordered().title(" Moves ")),
+            moves_area,
+        );
+    }
+
+    fn board_lines(&self) -> Vec<Line<'static>> {
+        let show_legal = self.board.player_to_move == Player::X && self.search.is_none();
+        let mut lines = Vec::new();
+        for row in 0..9 {
+            if row == 3 || row == 6 {
+                lines.push(Line::from("─────────┼─────────┼─────────"));
+            }
+            let mut spans = Vec::new();
+            for col in 0..9 {
+                if col == 3 || col == 6 {
+                    spans.push(Span::raw("│"));
+                }
+                let m = Move::from_global(row, col);
+                let (text, mut style) = match self.board.cell(m.major, m.minor) {
+                    Some(Player::X) => (" X ", Style::new().fg(Color::Red)),
+                    Some(Player::O) => (" O ", Style::new().fg(Color::Blue)),
+                    None => (" · ", Style::new().fg(Color::DarkGray)),
+                };
+                match self.board.sub_board_winner(m.major) {
+                    Some(Outcome::X) => style = style.bg(Color::Rgb(80, 30, 30)),
+                    Some(Outcome::O) => style = style.bg(Color::Rgb(30, 40, 80)),
+                    _ if show_legal && self.board.is_legal(m) => {
+                        style = style.bg(Color::Rgb(50, 60, 30))
+                    }
+                    _ => {}
+                }
+                if self.cursor == (row, col) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                spans.push(Span::styled(text, style));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal) -> io::Result<()> {
+    let mut app = App::new(1);
+    loop {
+        app.poll_search();
+        terminal.draw(|frame| app.draw(frame))?;
+        if event::poll(POLL_INTERVAL)? && !app.handle_event(event::read()?) {
+            return Ok(());
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    execute!(io::stdout(), EnableMouseCapture)?;
+    let result = run(&mut terminal);
+    execute!(io::stdout(), DisableMouseCapture)?;
+    ratatui::restore();
+    result
+}
@@ -0,0 +1,449 @@
+use std::io::IsTerminal;
+
+use instant::Instant;
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
+use uttt_core::*;
+use uttt_engine::*;
+
+mod tournament;
+
+/// Seed used for generating the benchmark positions and seeding the engine searching them.
+const BENCH_SEED: u64 = 0x5eed;
+/// Number of positions searched by the benchmark.
+const BENCH_POSITIONS: usize = 8;
+/// Number of plies played between two consecutive benchmark positions.
+const BENCH_PLIES_BETWEEN_POSITIONS: usize = 4;
+/// Number of MCTS iterations performed on each benchmark position.
+const BENCH_ITERATIONS: u32 = 20_000;
+/// Number of MCTS iterations per move in the games mined for puzzles.
+const PUZZLE_ITERATIONS: u32 = 500;
+/// Strength passed to [`MctsEngine::human_move`] in the games mined for puzzles, so that the games
+/// differ from day to day.
+const PUZZLE_STRENGTH: f32 = 0.5;
+/// Probability of letting the engine pick a move in a book position while training the book, so
+/// that new moves get tried.
+const BOOK_EXPLORATION: f64 = 0.2;
+/// Number of MCTS iterations per move while training the book.
+const BOOK_ITERATIONS: u32 = 2000;
+/// Puzzles are only taken from positions after this many plies.
+const PUZZLE_MIN_PLIES: usize = 10;
+/// Hardest supported puzzle difficulty, i.e. the number of moves of the solution.
+const PUZZLE_MAX_DIFFICULTY: u32 = 3;
+/// Default node budget of `solve`.
+const SOLVE_NODES: u64 = 2_000_000;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("bench") => bench(),
+        Some("book") => book(&args[1..]),
+        Some("calibrate") => calibrate(),
+        Some("perft") => perft(&args[1..]),
+        Some("puzzle") => puzzle(&args[1..]),
+        Some("repro") => repro(&args[1..]),
+        Some("solve") => solve(&args[1..]),
+        Some("tournament") => tournament(&args[1..]),
+        None => self_play(),
+        Some(command) => {
+            eprintln!("Unknown command: {}", command);
+            usage();
+        }
+    }
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: uttt-cli [bench|book <file> <games>|calibrate|perft <depth> [threads]|puzzle <yyyy-mm-dd> [difficulty]|repro <file>|solve <position> [nodes]|tournament <ratings-file> <games> [--openings <plies>] <player>...]"
+    );
+    std::process::exit(1);
+}
+
+/// Plays the engine against a random mover and prints the average number of simulated moves.
+fn self_play() {
+    let mut total_move_counts = Vec::new();
+    for _i in 0..100 {
+        let mut board = Board::with_rules(Rules {
+            strict_game_over: true,
+        });
+        let mut moves = board.generate_moves();
+
+        let mut move_counts = Vec::new();
+
+        let mut rng = thread_rng();
+        // The tree of every search is reused for the next one.
+        let mut mcts = MctsEngine::for_position(board);
+
+        while !moves.is_empty() {
+            let m = match board.player_to_move {
+                Player::X => {
+                    let (_iters, move_count) = mcts.run_search(1);
+                    move_counts.push(move_count);
+                    mcts.best_move().expect("game is in progress")
+                }
+                Player::O => *moves.choose(&mut rng).expect("moves is not empty"),
+            };
+            mcts.advance_root(m).expect("move is legal");
+            board = board.advance_state(m).unwrap();
+            moves = board.generate_moves();
+        }
+        let avg_move_count = move_counts.iter().sum::<u32>() / move_counts.len() as u32;
+        println!(
+            "Winner: {:?}\tAvg. move count: {}",
+            board.winner().expect("game is decided"),
+            avg_move_count
+        );
+        total_move_counts.push(avg_move_count);
+    }
+    let total_avg_move_count =
+        total_move_counts.iter().sum::<u32>() / total_move_counts.len() as u32;
+    println!("Total avg. move count: {}", total_avg_move_count);
+}
+
+/// Generates the fixed set of positions used by [`bench`].
+///
+/// The positions are taken at regular intervals from a single random game generated with
+/// [`BENCH_SEED`], so they are the same on every run.
+fn bench_positions() -> Vec<Board> {
+    let mut rng = StdRng::seed_from_u64(BENCH_SEED);
+    let mut board = Board::new();
+    let mut positions = Vec::new();
+    while positions.len() < BENCH_POSITIONS && board.winner().is_none() {
+        positions.push(board);
+        for _ in 0..BENCH_PLIES_BETWEEN_POSITIONS {
+            let moves = board.generate_moves();
+            let Some(m) = moves.choose(&mut rng) else {
+                break;
+            };
+            board = board.advance_state(*m).unwrap();
+            if board.winner().is_some() {
+                break;
+            }
+        }
+    }
+    positions
+}
+
+/// Runs a fixed workload and prints the total number of nodes (simulated moves), playouts and
+/// nodes per second. The output format is stable so that results can be compared across
+/// machines and patches, and so are the node and playout counts, since every search is seeded
+/// with [`BENCH_SEED`].
+fn bench() {
+    let positions = bench_positions();
+
+    let mut playouts = 0u64;
+    let mut nodes = 0u64;
+    let start = Instant::now();
+    for board in &positions {
+        let mut mcts = MctsEngine::with_seed(*board, BENCH_SEED);
+        let (_iters, moves) = mcts.run_iterations(BENCH_ITERATIONS);
+        playouts += BENCH_ITERATIONS as u64;
+        nodes += moves as u64;
+    }
+    let elapsed = start.elapsed();
+    let nps = (nodes as f64 / elapsed.as_secs_f64()) as u64;
+
+    println!("positions: {}", positions.len());
+    println!("playouts: {}", playouts);
+    println!("nodes: {}", nodes);
+    println!("time: {}ms", elapsed.as_millis());
+    println!("nps: {}", nps);
+}
+
+/// Measures the search speed of this machine and prints it along with the number of iterations
+/// corresponding to a few strengths.
+fn calibrate() {
+    let calibration = MctsEngine::calibrate();
+    println!("iterations/s: {:.0}", calibration.iterations_per_sec);
+    for strength in [50, 100, 500, 1000] {
+        let iterations = Calibration::iterations_for_strength(strength);
+        println!(
+            "strength {}: {} iterations, ~{}ms",
+            strength,
+            iterations,
+            calibration.millis_for_iterations(iterations)
+        );
+    }
+}
+
+/// Counts the positions reachable from the initial position at each depth up to the requested
+/// one. The work is split across threads (all available cores by default).
+fn perft(args: &[String]) {
+    let Some(Ok(depth)) = args.first().map(|arg| arg.parse::<u32>()) else {
+        usage();
+    };
+    let threads = match args.get(1).map(|arg| arg.parse::<usize>()) {
+        None => 0,
+        Some(Ok(threads)) => threads,
+        Some(Err(_)) => usage(),
+    };
+
+    let board = Board::new();
+    for depth in 1..=depth {
+        let start = Instant::now();
+        let nodes = board.perft_parallel(depth, threads);
+        println!(
+            "perft({}): {}\t{}ms",
+            depth,
+            nodes,
+            start.elapsed().as_millis()
+        );
+    }
+}
+
+/// Replays a search from a repro bundle and checks that it picks the same move.
+fn repro(args: &[String]) {
+    let [path] = args else {
+        usage();
+    };
+    let bundle: ReproBundle = match std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|s| s.parse().map_err(|err: ReproError| err.to_string()))
+    {
+        Ok(bundle) => bundle,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path, err);
+            std::process::exit(1);
+        }
+    };
+
+    print!("{}", render(&bundle.position));
+    let replayed = bundle.replay().best_move().ok();
+    let format = |m: Option<Move>| m.map_or("none".to_string(), |m| m.to_string());
+    println!("recorded best move: {}", format(bundle.best_move));
+    println!("replayed best move: {}", format(replayed));
+    if replayed != bundle.best_move {
+        eprintln!("Replayed search does not match the recorded one.");
+        std::process::exit(1);
+    }
+}
+
+/// Proves the outcome of a position, given in the notation of [`Board::from_notation`], with
+/// proof-number search.
+fn solve(args: &[String]) {
+    let (position, nodes) = match args {
+        [position] => (position, SOLVE_NODES),
+        [position, nodes] => match nodes.parse() {
+            Ok(nodes) => (position, nodes),
+            Err(_) => usage(),
+        },
+        _ => usage(),
+    };
+    let board = match Board::from_notation(position) {
+        Ok(board) => board,
+        Err(err) => {
+            eprintln!("Invalid position: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    print!("{}", render(&board));
+    let start = Instant::now();
+    let limits = SearchLimits {
+        nodes: Some(nodes),
+        ..SearchLimits::default()
+    };
+    let result = PnsEngine::for_position(board).solve(limits);
+    let player = board.player_to_move;
+    match (result.outcome, result.depth) {
+        (Some(outcome), Some(depth)) => {
+            let verdict = match outcome.player() {
+                Some(winner) if winner == player => "wins",
+                Some(_) => "loses",
+                None => "draws",
+            };
+            println!(
+                "{:?} {} with best play, proven {} plies deep",
+                player, verdict, depth
+            );
+        }
+        _ => println!("Not proven within {} nodes", nodes),
+    }
+    if let Some(m) = result.best_move {
+        println!("best move: {}", m);
+    }
+    println!(
+        "nodes: {}\ttime: {}ms",
+        result.nodes,
+        start.elapsed().as_millis()
+    );
+}
+
+/// Draws `board` for the terminal with its last move, in color if stdout is a terminal.
+fn render(board: &Board) -> String {
+    board.render(RenderOptions {
+        color: std::io::stdout().is_terminal(),
+        last_move: board.last_move(),
+        ..Default::default()
+    })
+}
+
+/// Returns `true` if the game is won by `player`.
+fn is_won_by(board: &Board, player: Player) -> bool {
+    board.winner() == Some(player.into())
+}
+
+/// Returns `true` if playing `m` wins the game for the player to move within `moves` of their own
+/// moves, whatever the opponent replies.
+fn forces_win(board: &Board, m: Move, moves: u32) -> bool {
+    let player = board.player_to_move;
+    let next = board.advance_state(m).expect("move is legal");
+    if is_won_by(&next, player) {
+        return true;
+    }
+    if moves <= 1 || next.winner().is_some() {
+        return false;
+    }
+    next.moves().all(|reply| {
+        let next = next.advance_state(reply).expect("move is legal");
+        next.moves().any(|m| forces_win(&next, m, moves - 1))
+    })
+}
+
+/// Returns the solution if the position is a puzzle of the given difficulty: the player to move
+/// can force a win in `difficulty` moves with exactly one first move, and cannot win faster.
+fn puzzle_solution(board: &Board, difficulty: u32) -> Option<Move> {
+    let moves = board.generate_moves();
+    if difficulty > 1 && moves.iter().any(|&m| forces_win(board, m, difficulty - 1)) {
+        return None;
+    }
+    let mut solutions = moves
+        .into_iter()
+        .filter(|&m| forces_win(board, m, difficulty));
+    let solution = solutions.next()?;
+    solutions.next().is_none().then_some(solution)
+}
+
+/// Derives a puzzle from a date, so that running the command on the same day always prints the
+/// same puzzle. Self-play games seeded with the date are mined for positions which are verified
+/// by an exhaustive search to have a unique solution.
+fn puzzle(args: &[String]) {
+    let Some(date) = args.first() else {
+        usage();
+    };
+    let seed = match date
+        .split('-')
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .as_deref()
+    {
+        Ok(&[year, month, day]) if (1..=12).contains(&month) && (1..=31).contains(&day) => {
+            year * 10_000 + month * 100 + day
+        }
+        _ => usage(),
+    };
+    let difficulty = match args.get(1).map(|arg| arg.parse::<u32>()) {
+        None => 1,
+        Some(Ok(difficulty)) if (1..=PUZZLE_MAX_DIFFICULTY).contains(&difficulty) => difficulty,
+        Some(_) => usage(),
+    };
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    loop {
+        let mut board = Board::with_rules(Rules {
+            strict_game_over: true,
+        });
+        let mut history = Vec::new();
+        while board.count_moves() > 0 {
+            if history.len() >= PUZZLE_MIN_PLIES {
+                if let Some(solution) = puzzle_solution(&board, difficulty) {
+                    let moves: Vec<_> = history.iter().map(ToString::to_string).collect();
+                    println!("puzzle {}", date);
+                    println!("difficulty {}", difficulty);
+                    println!("moves {}", moves.join(" "));
+                    println!("to-move {:?}", board.player_to_move);
+                    println!("solution {}", solution);
+                    println!();
+                    print!("{}", render(&board));
+                    return;
+                }
+            }
+            let mut mcts = MctsEngine::with_seed(board, rng.gen());
+            mcts.run_iterations(PUZZLE_ITERATIONS);
+            let m = mcts
+                .human_move(PUZZLE_STRENGTH)
+                .expect("game is in progress");
+            board = board.advance_state(m).unwrap();
+            history.push(m);
+        }
+    }
+}
+
+/// Runs a round-robin tournament, see [`tournament::tournament`].
+fn tournament(args: &[String]) {
+    let [path, games, players @ ..] = args else {
+        usage();
+    };
+    let Ok(games) = games.parse() else {
+        usage();
+    };
+    let (opening_plies, players) = match players {
+        [flag, plies, players @ ..] if flag == "--openings" => match plies.parse() {
+            Ok(plies) => (Some(plies), players),
+            Err(_) => usage(),
+        },
+        players => (None, players),
+    };
+    if players.len() < 2 {
+        usage();
+    }
+    if let Err(err) = tournament::tournament(path, games, opening_plies, players) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Plays self-play games using the opening book at `path` (which is created if missing) and
+/// records them back into it, so that the book improves every time this is run.
+fn book(args: &[String]) {
+    let [path, games] = args else {
+        usage();
+    };
+    let Ok(games) = games.parse::<u32>() else {
+        usage();
+    };
+    let mut book = match std::fs::read_to_string(path) {
+        Ok(s) => match s.parse::<OpeningBook>() {
+            Ok(book) => book,
+            Err(err) => {
+                eprintln!("Failed to read {}: {}", path, err);
+                std::process::exit(1);
+            }
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => OpeningBook::new(),
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path, err);
+            std::process::exit(1);
+        }
+    };
+
+    let mut rng = thread_rng();
+    for _ in 0..games {
+        let mut board = Board::with_rules(Rules {
+            strict_game_over: true,
+        });
+        let mut moves = Vec::new();
+        while board.count_moves() > 0 {
+            let book_move = (moves.len() < BOOK_DEFAULT_PLIES && !rng.gen_bool(BOOK_EXPLORATION))
+                .then(|| book.sample_move(&board, &mut rng))
+                .flatten();
+            let m = book_move.unwrap_or_else(|| {
+                let mut mcts = MctsEngine::for_position(board);
+                mcts.run_iterations(BOOK_ITERATIONS);
+                mcts.best_move().expect("game is in progress")
+            });
+            board = board.advance_state(m).unwrap();
+            moves.push(m);
+        }
+        let outcome = board.winner().expect("game is decided");
+        book.record_game(&moves, outcome, BOOK_DEFAULT_PLIES);
+        println!("Winner: {:?}", outcome);
+    }
+
+    if let Err(err) = std::fs::write(path, book.to_string()) {
+        eprintln!("Failed to write {}: {}", path, err);
+        std::process::exit(1);
+    }
+    println!("Book positions: {}", book.len());
+}
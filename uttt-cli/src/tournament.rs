@@ -0,0 +1,383 @@
+//! Round-robin tournaments between engine configurations with a persistent rating pool.
+//!
+//! The ratings file stores the accumulated results of every pair of players, one line per pair:
+//! `<player> <opponent> <wins> <draws> <losses>`. Every run adds its results to the file, and the
+//! ratings are fitted to all results at once, so runs from different days (or engine versions)
+//! end up in a single pool.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use rand::prelude::SliceRandom;
+use rand::thread_rng;
+use uttt_core::{Board, Move, Outcome, Player, Rules};
+use uttt_engine::{
+    AlphaBetaEngine, Engine, EngineError, MctsEngine, SearchLimits, BEGINNER_MISTAKE_RATE,
+};
+
+/// Rating of the average player of the pool.
+const MEAN_RATING: f64 = 1500.0;
+/// Number of iterations of the rating fit.
+const FIT_ITERATIONS: usize = 1000;
+
+/// A player of the tournament, parsed from `[<name>=]<kind>[:<args>]`.
+struct Contestant {
+    name: String,
+    kind: Kind,
+}
+
+enum Kind {
+    Random,
+    Mcts {
+        iterations: u32,
+    },
+    Beginner {
+        iterations: u32,
+    },
+    Human {
+        iterations: u32,
+        strength: f32,
+    },
+    /// The engine is kept for all games of the player, so that its transposition table is only
+    /// allocated once.
+    AlphaBeta {
+        depth: u32,
+        engine: Box<AlphaBetaEngine>,
+    },
+}
+
+impl Contestant {
+    /// Parses a contestant. The name defaults to the specification itself, so that results of the
+    /// same configuration accumulate across runs. An explicit name can be given to tell engine
+    /// versions apart, e.g. `v2=mcts:1000`.
+    fn parse(s: &str) -> Option<Self> {
+        let (name, spec) = s.split_once('=').unwrap_or((s, s));
+        let mut parts = spec.split(':');
+        let kind = match (parts.next()?, parts.next(), parts.next()) {
+            ("random", None, None) => Kind::Random,
+            ("mcts", Some(iterations), None) => Kind::Mcts {
+                iterations: iterations.parse().ok()?,
+            },
+            ("beginner", Some(iterations), None) => Kind::Beginner {
+                iterations: iterations.parse().ok()?,
+            },
+            ("human", Some(iterations), Some(strength)) => Kind::Human {
+                iterations: iterations.parse().ok()?,
+                strength: strength.parse().ok()?,
+            },
+            ("alphabeta", Some(depth), None) => Kind::AlphaBeta {
+                depth: depth.parse().ok()?,
+                engine: Box::new(AlphaBetaEngine::for_position(Board::new())),
+            },
+            _ => return None,
+        };
+        if name.is_empty() || name.contains(char::is_whitespace) || parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            name: name.to_string(),
+            kind,
+        })
+    }
+
+    fn pick_move(&mut self, board: Board) -> Move {
+        let search = |iterations| {
+            let mut mcts = MctsEngine::for_position(board);
+            mcts.run_iterations(iterations);
+            mcts
+        };
+        match &mut self.kind {
+            Kind::Random => board
+                .generate_moves()
+                .choose(&mut thread_rng())
+                .copied()
+                .ok_or(EngineError::NoLegalMoves),
+            &mut Kind::Mcts { iterations } => {
+                let limits = SearchLimits {
+                    nodes: Some(iterations as u64),
+                    ..SearchLimits::default()
+                };
+                MctsEngine::for_position(board)
+                    .go(limits)
+                    .best_move
+                    .ok_or(EngineError::NoLegalMoves)
+            }
+            &mut Kind::Beginner { iterations } => {
+                search(iterations).beginner_move(BEGINNER_MISTAKE_RATE)
+            }
+            &mut Kind::Human {
+                iterations,
+                strength,
+            } => search(iterations).human_move(strength),
+            Kind::AlphaBeta { depth, engine } => {
+                let limits = SearchLimits {
+                    depth: Some(*depth),
+                    ..SearchLimits::default()
+                };
+                engine.set_position(board);
+                engine.go(limits).best_move.ok_or(EngineError::NoLegalMoves)
+            }
+        }
+        .expect("game is in progress")
+    }
+}
+
+/// Accumulated results of a player against an opponent.
+#[derive(Default, Clone, Copy)]
+struct Record {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+}
+
+impl Record {
+    fn games(self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+}
+
+/// Results of all pairs of players, keyed by `(player, opponent)` with `player < opponent`.
+type Results = BTreeMap<(String, String), Record>;
+
+fn parse_results(s: &str) -> Result<Results, String> {
+    let mut results = Results::new();
+    for (i, line) in s
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+    {
+        let invalid = || format!("invalid line {}: {}", i + 1, line);
+        let fields: Vec<_> = line.split_whitespace().collect();
+        let [player, opponent, wins, draws, losses] = fields[..] else {
+            return Err(invalid());
+        };
+        let count = |s: &str| s.parse::<u32>().map_err(|_| invalid());
+        let record = Record {
+            wins: count(wins)?,
+            draws: count(draws)?,
+            losses: count(losses)?,
+        };
+        add_result(&mut results, player, opponent, record);
+    }
+    Ok(results)
+}
+
+fn format_results(results: &Results) -> String {
+    let mut s = String::new();
+    for ((player, opponent), record) in results {
+        let Record {
+            wins,
+            draws,
+            losses,
+        } = record;
+        writeln!(s, "{player} {opponent} {wins} {draws} {losses}").unwrap();
+    }
+    s
+}
+
+/// Adds the results of `player` against `opponent`.
+fn add_result(results: &mut Results, player: &str, opponent: &str, record: Record) {
+    let (key, record) = if player < opponent {
+        ((player, opponent), record)
+    } else {
+        let Record {
+            wins,
+            draws,
+            losses,
+        } = record;
+        let flipped = Record {
+            wins: losses,
+            draws,
+            losses: wins,
+        };
+        ((opponent, player), flipped)
+    };
+    let total = results
+        .entry((key.0.to_string(), key.1.to_string()))
+        .or_default();
+    total.wins += record.wins;
+    total.draws += record.draws;
+    total.losses += record.losses;
+}
+
+/// A fitted rating with the half width of its 95% confidence interval.
+struct Rating {
+    name: String,
+    rating: f64,
+    error: f64,
+    games: u32,
+}
+
+/// Fits Elo ratings to the results with the Bradley-Terry model, counting draws as half a win.
+///
+/// Every player gets one virtual draw against a player of strength `1`, which keeps the ratings of
+/// players who won or lost all their games finite. The errors are derived from the Fisher
+/// information of each rating, keeping the other ratings fixed.
+fn fit_ratings(results: &Results) -> Vec<Rating> {
+    let mut names: Vec<&str> = results
+        .keys()
+        .flat_map(|(player, opponent)| [player.as_str(), opponent.as_str()])
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    let index = |name: &str| names.binary_search(&name).unwrap();
+
+    let n = names.len();
+    // Score (wins + draws / 2) and number of games of every ordered pair.
+    let mut score = vec![vec![0.0; n]; n];
+    let mut games = vec![vec![0.0; n]; n];
+    for ((player, opponent), record) in results {
+        let (i, j) = (index(player), index(opponent));
+        let draws = record.draws as f64 / 2.0;
+        score[i][j] += record.wins as f64 + draws;
+        score[j][i] += record.losses as f64 + draws;
+        games[i][j] += record.games() as f64;
+        games[j][i] += record.games() as f64;
+    }
+
+    // Minorization-maximization updates of the strengths.
+    let mut gamma = vec![1.0; n];
+    for _ in 0..FIT_ITERATIONS {
+        for i in 0..n {
+            let wins = 0.5 + score[i].iter().sum::<f64>();
+            let denominator = 1.0 / (gamma[i] + 1.0)
+                + (0..n)
+                    .filter(|&j| j != i)
+                    .map(|j| games[i][j] / (gamma[i] + gamma[j]))
+                    .sum::<f64>();
+            gamma[i] = wins / denominator;
+        }
+    }
+
+    let scale = 400.0 / std::f64::consts::LN_10;
+    let mean = gamma.iter().map(|g| g.ln()).sum::<f64>() / n as f64;
+    let mut ratings: Vec<_> = (0..n)
+        .map(|i| {
+            let p = |j: f64| gamma[i] / (gamma[i] + j);
+            let information = p(1.0) * (1.0 - p(1.0))
+                + (0..n)
+                    .filter(|&j| j != i)
+                    .map(|j| games[i][j] * p(gamma[j]) * (1.0 - p(gamma[j])))
+                    .sum::<f64>();
+            Rating {
+                name: names[i].to_string(),
+                rating: MEAN_RATING + scale * (gamma[i].ln() - mean),
+                error: 1.96 * scale / information.sqrt(),
+                games: games[i].iter().sum::<f64>() as u32,
+            }
+        })
+        .collect();
+    ratings.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+    ratings
+}
+
+/// Plays a game from the given position and returns the outcome.
+fn play_game(mut board: Board, x: &mut Contestant, o: &mut Contestant) -> Outcome {
+    while board.count_moves() > 0 {
+        let m = match board.player_to_move {
+            Player::X => x.pick_move(board),
+            Player::O => o.pick_move(board),
+        };
+        board = board.advance_state(m).unwrap();
+    }
+    board.winner().expect("game is decided")
+}
+
+/// Generates `count` openings by playing `plies` random moves from the initial position.
+fn random_openings(count: u32, plies: usize) -> Vec<Board> {
+    let mut rng = thread_rng();
+    (0..count)
+        .map(|_| {
+            let mut board = Board::with_rules(Rules {
+                strict_game_over: true,
+            });
+            for _ in 0..plies {
+                let moves = board.generate_moves();
+                // Stop before the game is decided, so that there is something left to play.
+                let Some(&m) = moves.choose(&mut rng) else {
+                    break;
+                };
+                let next = board.advance_state(m).unwrap();
+                if next.winner().is_some() {
+                    break;
+                }
+                board = next;
+            }
+            board
+        })
+        .collect()
+}
+
+/// Plays `games` games between every pair of contestants (alternating who moves first), adds the
+/// results to the ratings file and prints the ratings of the whole pool.
+///
+/// If `opening_plies` is given, the games start from a set of random openings with that many
+/// plies instead of the initial position. Every pair plays each opening twice, once from each
+/// side, and all pairs play the same openings.
+pub fn tournament(
+    path: &str,
+    games: u32,
+    opening_plies: Option<usize>,
+    specs: &[String],
+) -> Result<(), String> {
+    let mut contestants = specs
+        .iter()
+        .map(|spec| Contestant::parse(spec).ok_or_else(|| format!("invalid player: {}", spec)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut results = match std::fs::read_to_string(path) {
+        Ok(s) => parse_results(&s)?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Results::new(),
+        Err(err) => return Err(format!("failed to read {}: {}", path, err)),
+    };
+
+    let openings = match opening_plies {
+        Some(plies) => random_openings(games.div_ceil(2), plies),
+        None => vec![Board::with_rules(Rules {
+            strict_game_over: true,
+        })],
+    };
+
+    for i in 0..contestants.len() {
+        for j in i + 1..contestants.len() {
+            let (left, right) = contestants.split_at_mut(j);
+            let (a, b) = (&mut left[i], &mut right[0]);
+            let mut record = Record::default();
+            for game in 0..games {
+                let a_is_x = game % 2 == 0;
+                let (x, o) = if a_is_x {
+                    (&mut *a, &mut *b)
+                } else {
+                    (&mut *b, &mut *a)
+                };
+                let opening = openings[(game / 2) as usize % openings.len()];
+                match (play_game(opening, x, o), a_is_x) {
+                    (Outcome::Tie, _) => record.draws += 1,
+                    (Outcome::X, true) | (Outcome::O, false) => record.wins += 1,
+                    (Outcome::X, false) | (Outcome::O, true) => record.losses += 1,
+                }
+            }
+            println!(
+                "{} vs {}: +{} ={} -{}",
+                a.name, b.name, record.wins, record.draws, record.losses
+            );
+            add_result(&mut results, &a.name, &b.name, record);
+        }
+    }
+
+    std::fs::write(path, format_results(&results))
+        .map_err(|err| format!("failed to write {}: {}", path, err))?;
+
+    println!();
+    println!("{:<24} {:>6} {:>6} {:>6}", "player", "elo", "±95%", "games");
+    for Rating {
+        name,
+        rating,
+        error,
+        games,
+    } in fit_ratings(&results)
+    {
+        println!("{name:<24} {rating:>6.0} {error:>6.0} {games:>6}");
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+//! Feeds arbitrary moves, including out of range ones, through `Board::advance_state` and checks
+//! that exactly the generated moves are accepted. Board invariants are checked after every move
+//! by the `strict-checks` feature.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use uttt_core::{Board, Move};
+
+/// Decodes a byte into a move. Most bytes map to one of the 81 cells, the rest map to moves with an
+/// out of range major index.
+fn decode_move(byte: u8) -> Move {
+    if byte < 243 {
+        let index = byte as u32 % 81;
+        Move {
+            major: index / 9,
+            minor: index % 9,
+        }
+    } else {
+        Move {
+            major: byte as u32 - 234,
+            minor: byte as u32 % 9,
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut board = Board::new();
+    for &byte in data {
+        let m = decode_move(byte);
+        let legal = board.generate_moves().contains(&m);
+        match board.advance_state(m) {
+            Some(next) => {
+                assert!(legal, "advance_state accepted a move that was not generated");
+                board = next;
+            }
+            None => assert!(!legal, "advance_state rejected a generated move"),
+        }
+    }
+});
@@ -0,0 +1,79 @@
+//! Plays the same moves on a `Board` and on the reference `SimpleBoard` and checks that both agree
+//! on the board contents, move generation, move legality and the winner after every move.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use uttt_core::{Board, Move, Outcome, Player, Rules, SimpleBoard};
+
+/// Returns the state of a sub-board according to `Board::sub_wins`.
+fn sub_board_winner(board: &Board, major: u32) -> Option<Outcome> {
+    let mask = 1 << major;
+    if board.sub_wins.x.0 & mask != 0 {
+        Some(Outcome::X)
+    } else if board.sub_wins.o.0 & mask != 0 {
+        Some(Outcome::O)
+    } else if board.sub_wins.tie.0 & mask != 0 {
+        Some(Outcome::Tie)
+    } else {
+        None
+    }
+}
+
+fn assert_same(board: &Board, simple: &SimpleBoard) {
+    assert_eq!(board.player_to_move, simple.player_to_move());
+    for major in 0..9 {
+        let sub_board = board.board[major as usize];
+        for minor in 0..9 {
+            let cell = if sub_board.x.0 & 1 << minor != 0 {
+                Some(Player::X)
+            } else if sub_board.o.0 & 1 << minor != 0 {
+                Some(Player::O)
+            } else {
+                None
+            };
+            assert_eq!(cell, simple.cell(major, minor), "cell ({major}, {minor}) differs");
+        }
+        assert_eq!(
+            sub_board_winner(board, major),
+            simple.sub_board_winner(major),
+            "sub-board {major} differs"
+        );
+    }
+    assert_eq!(
+        u8::from(board.constraint()) as u32,
+        simple.next_sub_board().unwrap_or(9)
+    );
+    assert_eq!(board.winner(), simple.winner());
+    assert!(board.generate_moves() == simple.legal_moves(), "legal moves differ");
+}
+
+fuzz_target!(|data: &[u8]| {
+    // The reference does not accept moves once the game is decided.
+    let mut board = Board::with_rules(Rules {
+        strict_game_over: true,
+    });
+    let mut simple = SimpleBoard::new();
+    assert_same(&board, &simple);
+
+    for &byte in data {
+        // Bytes below 81 are played as is, including illegal moves. Other bytes pick one of the
+        // legal moves so that games get deep enough to be decided.
+        let m = if byte < 81 {
+            Move::new(byte as u32 / 9, byte as u32 % 9)
+        } else {
+            let moves = simple.legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            moves[byte as usize % moves.len()]
+        };
+
+        let next = board.advance_state(m);
+        assert_eq!(next.is_some(), simple.play(m), "legality differs");
+        if let Some(next) = next {
+            board = next;
+            assert_same(&board, &simple);
+        }
+    }
+});
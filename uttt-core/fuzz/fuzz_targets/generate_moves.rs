@@ -0,0 +1,34 @@
+//! Plays games chosen by the fuzzer and checks the output of `Board::generate_moves_in_place` in
+//! every position against a brute force search over all 81 cells.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use uttt_core::{Board, Move};
+
+fuzz_target!(|data: &[u8]| {
+    let mut board = Board::new();
+    for &byte in data {
+        if board.winner().is_some() {
+            break;
+        }
+
+        let mut buf = [Move::new(0, 0); 81];
+        let moves = board.generate_moves_in_place(&mut buf);
+        assert!(moves == board.generate_moves().as_slice());
+
+        // Every generated move is accepted and unique.
+        for (i, m) in moves.iter().enumerate() {
+            assert!(board.advance_state(*m).is_some(), "generated an illegal move");
+            assert!(!moves[..i].contains(m), "generated a duplicate move");
+        }
+        // Every accepted move is generated.
+        let legal_count = (0..81)
+            .filter(|i| board.advance_state(Move::new(i / 9, i % 9)).is_some())
+            .count();
+        assert_eq!(moves.len(), legal_count, "missed a legal move");
+
+        let m = moves[byte as usize % moves.len()];
+        board = board.advance_state(m).unwrap();
+    }
+});
@@ -0,0 +1,35 @@
+//! Parses arbitrary strings with `Board::from_notation` and checks that every accepted position
+//! round-trips through `Board::to_notation`, and that positions reached by arbitrary moves are
+//! accepted. Board invariants are checked after every move by the `strict-checks` feature.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use uttt_core::{Board, Move};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(board) = Board::from_notation(s) {
+            let notation = board.to_notation();
+            assert!(
+                Board::from_notation(&notation) == Ok(board),
+                "notation `{}` does not round-trip",
+                notation
+            );
+        }
+    }
+
+    let mut board = Board::new();
+    for &byte in data {
+        let index = byte as u32 % 81;
+        let Some(next) = board.advance_state(Move::new(index / 9, index % 9)) else {
+            continue;
+        };
+        board = next;
+        assert!(
+            Board::from_notation(&board.to_notation()) == Ok(board),
+            "position `{}` is rejected",
+            board.to_notation()
+        );
+    }
+});
@@ -0,0 +1,84 @@
+//! Parsing boards printed as a 9x9 grid, so that a board from a log or a terminal can be pasted
+//! back in.
+//!
+//! Every line with cells is a row of the grid, from top to bottom. Cells are `X` and `O`, and
+//! empty cells are any of `_`, `.`, `·`, `•` and `*`. Everything else on the line, such as
+//! whitespace, borders, labels and brackets, is ignored, so the output of the [`Display`] impl of
+//! [`Board`] and of [`Board::render`] is accepted. Lines without cells are skipped.
+//!
+//! One line holds the player to move and the sub-board they have to move in, written as the last
+//! two fields of the [notation](Board::to_notation), e.g. `O 4` or `X -`.
+//!
+//! [`Display`]: std::fmt::Display
+
+use crate::notation::{parse_constraint, parse_player};
+use crate::{Board, BoardBuilder, Move, NotationError, Player};
+
+/// Characters of empty cells.
+const EMPTY: [char; 5] = ['_', '.', '·', '•', '*'];
+
+impl Board {
+    /// Parses a board printed as a grid, as described in the [module documentation](self). The
+    /// returned board uses the default [`Rules`](crate::Rules).
+    ///
+    /// Besides the syntax, this checks that the position is consistent with
+    /// [`validate`](Self::validate).
+    pub fn parse_ascii(s: &str) -> Result<Self, NotationError> {
+        let mut builder = BoardBuilder::new();
+        let mut rows = 0;
+        let mut annotation = None;
+        for line in s.lines() {
+            let cells: Vec<_> = line
+                .chars()
+                .filter_map(|c| match c {
+                    'X' => Some(Some(Player::X)),
+                    'O' => Some(Some(Player::O)),
+                    c if EMPTY.contains(&c) => Some(None),
+                    _ => None,
+                })
+                .collect();
+            let fields: Vec<_> = line.split_whitespace().collect();
+            match (cells.len(), &fields[..]) {
+                (0, _) => {}
+                (9, _) => {
+                    if rows == 9 {
+                        return Err(NotationError::Invalid(
+                            "expected 9 rows of cells".to_string(),
+                        ));
+                    }
+                    for (col, player) in cells.into_iter().enumerate() {
+                        if let Some(player) = player {
+                            let m = Move::from_global(rows, col as u32);
+                            builder = builder.place(m.major, m.minor, player);
+                        }
+                    }
+                    rows += 1;
+                }
+                (_, &[player, next_sub_board]) if annotation.is_none() => {
+                    annotation = Some((parse_player(player)?, parse_constraint(next_sub_board)?));
+                }
+                _ => {
+                    return Err(NotationError::Invalid(format!(
+                        "line `{}` is neither a row of 9 cells nor the player to move",
+                        line.trim()
+                    )))
+                }
+            }
+        }
+        if rows != 9 {
+            return Err(NotationError::Invalid(format!(
+                "expected 9 rows of cells, found {}",
+                rows
+            )));
+        }
+        let Some((player, constraint)) = annotation else {
+            return Err(NotationError::Invalid(
+                "expected a line with the player to move and the next sub-board".to_string(),
+            ));
+        };
+        Ok(builder
+            .player_to_move(player)
+            .constraint(constraint)
+            .build()?)
+    }
+}
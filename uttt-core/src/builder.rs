@@ -0,0 +1,106 @@
+//! Setting up arbitrary positions cell by cell, e.g. for puzzles or positions from other apps.
+
+use crate::{Board, NextSubBoard, Player, PositionError, Rules};
+
+/// Builds a [`Board`] by placing stones one at a time. The position is only checked by
+/// [`build`](Self::build), so stones can be placed in any order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardBuilder {
+    board: Board,
+    player_to_move: Option<Player>,
+}
+
+impl BoardBuilder {
+    /// Create a [`BoardBuilder`] for an empty board.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`Rules`] of the board.
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.board.rules = rules;
+        self
+    }
+
+    /// Places a stone of `player` in cell `minor` of sub-board `major`, replacing the stone that
+    /// was there.
+    ///
+    /// # Panics
+    /// This method panics if `major` or `minor` is not in the range `0..9`.
+    pub fn place(mut self, major: u32, minor: u32, player: Player) -> Self {
+        self = self.clear(major, minor);
+        self.board.board[major as usize][player].0 |= 1 << minor;
+        self
+    }
+
+    /// Empties cell `minor` of sub-board `major`.
+    ///
+    /// # Panics
+    /// This method panics if `major` or `minor` is not in the range `0..9`.
+    pub fn clear(mut self, major: u32, minor: u32) -> Self {
+        assert!(major < 9, "major index {} is out of range", major);
+        assert!(minor < 9, "minor index {} is out of range", minor);
+        let sub_board = &mut self.board.board[major as usize];
+        sub_board.x.0 &= !(1 << minor);
+        sub_board.o.0 &= !(1 << minor);
+        self
+    }
+
+    /// Sets the player to move. By default, this follows from the number of stones: X if both
+    /// players have as many stones and O otherwise.
+    pub fn player_to_move(mut self, player: Player) -> Self {
+        self.player_to_move = Some(player);
+        self
+    }
+
+    /// Sets where the player to move can play. Defaults to [`NextSubBoard::Any`].
+    ///
+    /// # Panics
+    /// This method panics if the sub-board index is greater than 8.
+    pub fn constraint(mut self, constraint: NextSubBoard) -> Self {
+        self.board.set_constraint(constraint);
+        self
+    }
+
+    /// Returns the position, or the reason why it cannot occur in a game as checked by
+    /// [`Board::validate`].
+    pub fn build(self) -> Result<Board, PositionError> {
+        let mut board = self.board;
+        board.player_to_move = self.player_to_move.unwrap_or_else(|| {
+            let count = |player| {
+                board
+                    .board
+                    .iter()
+                    .map(|sub_board| sub_board[player].0.count_ones())
+                    .sum::<u32>()
+            };
+            if count(Player::X) > count(Player::O) {
+                Player::O
+            } else {
+                Player::X
+            }
+        });
+        board.update_derived_state();
+        board.validate()?;
+        Ok(board)
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self {
+            board: Board::new(),
+            player_to_move: None,
+        }
+    }
+}
+
+/// Starts from an existing position, keeping its player to move.
+impl From<Board> for BoardBuilder {
+    fn from(board: Board) -> Self {
+        Self {
+            board,
+            player_to_move: Some(board.player_to_move),
+        }
+    }
+}
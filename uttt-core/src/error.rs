@@ -0,0 +1,131 @@
+//! Error types.
+
+use thiserror::Error;
+
+use crate::Player;
+
+/// Reason a [`Move`](crate::Move) cannot be played on a [`Board`](crate::Board).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum MoveError {
+    #[error("move ({major}, {minor}) is out of range")]
+    OutOfRange { major: u32, minor: u32 },
+    #[error("cell ({major}, {minor}) is already occupied")]
+    CellOccupied { major: u32, minor: u32 },
+    #[error("move must be played in sub-board {expected}, not {major}")]
+    WrongSubBoard { expected: u32, major: u32 },
+    #[error("sub-board {major} is already decided")]
+    SubBoardDecided { major: u32 },
+    /// Only returned with [`Rules::strict_game_over`](crate::Rules::strict_game_over).
+    #[error("game is already decided")]
+    GameOver,
+}
+
+impl MoveError {
+    /// Returns a short, stable name of the rule the move violates, e.g. for metrics and logs.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            MoveError::OutOfRange { .. } => "out-of-range",
+            MoveError::CellOccupied { .. } => "cell-occupied",
+            MoveError::WrongSubBoard { .. } => "wrong-sub-board",
+            MoveError::SubBoardDecided { .. } => "sub-board-decided",
+            MoveError::GameOver => "game-over",
+        }
+    }
+}
+
+/// Structured report of a move rejected by
+/// [`Board::advance_state_with_report`](crate::Board::advance_state_with_report).
+///
+/// The [`Display`](std::fmt::Display) impl formats the report as a single `key=value` line that
+/// is easy to grep and parse in server logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error(
+    "rejected move: rule={rule} major={major} minor={minor} player={player:?} \
+     next_sub_board={next_sub_board} position={position_hash:016x} reason=\"{error}\"",
+    rule = .error.rule()
+)]
+pub struct MoveRejection {
+    /// The rule the move violates, including the offending indices.
+    pub error: MoveError,
+    pub major: u32,
+    pub minor: u32,
+    /// The player who tried to move.
+    pub player: Player,
+    /// The sub-board the player had to move in, `9` if any.
+    pub next_sub_board: u32,
+    /// [`Board::snapshot_hash`](crate::Board::snapshot_hash) of the position the move was
+    /// rejected in.
+    pub position_hash: u64,
+}
+
+/// Error returned when parsing a value from a string.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseError {
+    #[error("invalid move `{0}`, expected `major,minor`")]
+    InvalidMove(String),
+    #[error(transparent)]
+    Move(#[from] MoveError),
+}
+
+/// Error returned by [`Board::from_moves`](crate::Board::from_moves) for the first illegal move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("move {} is illegal: {error}", .index + 1)]
+pub struct ReplayError {
+    /// Index of the illegal move, starting from `0`.
+    pub index: usize,
+    pub error: MoveError,
+}
+
+/// Inconsistency found by [`Board::validate`](crate::Board::validate).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum PositionError {
+    #[error("sub-board {major} has cells occupied by both players or out of range")]
+    InvalidCells { major: u32 },
+    #[error("sub-board {major} is won by both players")]
+    BothPlayersWon { major: u32 },
+    #[error("sub_wins does not match the cells of sub-board {major}")]
+    SubWinsMismatch { major: u32 },
+    #[error("X has {x} cells and O has {o}, which is impossible with {player:?} to move")]
+    WrongPlayerToMove { x: u32, o: u32, player: Player },
+    #[error("next sub-board {next_sub_board} is out of range")]
+    NextSubBoardOutOfRange { next_sub_board: u8 },
+    #[error("next sub-board {major} is already decided")]
+    NextSubBoardDecided { major: u32 },
+    #[error("no move of the previous player can send the game to sub-board {major}")]
+    NextSubBoardUnreachable { major: u32 },
+    #[error("no move of the previous player can let the player to move play anywhere")]
+    AnywhereUnreachable,
+    /// Fixed by [`Board::update_derived_state`](crate::Board::update_derived_state).
+    #[error("move count or Zobrist hash does not match the cells")]
+    StaleDerivedState,
+}
+
+/// Error returned by [`Board::from_notation`](crate::Board::from_notation) and
+/// [`Board::parse_ascii`](crate::Board::parse_ascii).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum NotationError {
+    #[error("invalid notation: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Position(#[from] PositionError),
+}
+
+/// Error returned by [`Board::unpack`](crate::Board::unpack).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum UnpackError {
+    #[error("unsupported packed board version {version}")]
+    UnsupportedVersion { version: u8 },
+    #[error("invalid packed board")]
+    InvalidEncoding,
+    #[error(transparent)]
+    Position(#[from] PositionError),
+}
+
+/// Error returned when importing a [`GameRecord`](crate::GameRecord).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ImportError {
+    #[error("invalid game record: {0}")]
+    InvalidFormat(String),
+    #[error(transparent)]
+    IllegalMove(#[from] ReplayError),
+}
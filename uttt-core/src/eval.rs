@@ -0,0 +1,127 @@
+//! Evaluation of positions without building a search tree.
+
+use rand::Rng;
+
+use crate::{BitBoard, Board, MoveList, NextSubBoard, Outcome, Player};
+
+/// Score of a won game in [`Board::evaluate`]. Every other score is much smaller.
+pub const EVAL_WIN: i32 = 1_000_000;
+
+const CENTER: u16 = 0b000010000;
+const CORNERS: u16 = 0b101000101;
+
+/// Weights of the features of [`Board::evaluate_with`]. Every weight is the score of one
+/// occurrence of the feature for a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalWeights {
+    /// A won sub-board.
+    pub sub_board: i32,
+    /// Added for a won center sub-board.
+    pub center_sub_board: i32,
+    /// Added for a won corner sub-board.
+    pub corner_sub_board: i32,
+    /// The center cell of an undecided sub-board.
+    pub center_cell: i32,
+    /// A corner cell of an undecided sub-board.
+    pub corner_cell: i32,
+    /// Two cells of a line in an undecided sub-board whose third cell is empty.
+    pub sub_board_two_in_a_row: i32,
+    /// Two won sub-boards of a line whose third sub-board is undecided.
+    pub two_in_a_row: i32,
+    /// For the player to move if they can move in any sub-board.
+    pub free_move: i32,
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self {
+            sub_board: 100,
+            center_sub_board: 30,
+            corner_sub_board: 15,
+            center_cell: 6,
+            corner_cell: 3,
+            sub_board_two_in_a_row: 12,
+            two_in_a_row: 150,
+            free_move: 25,
+        }
+    }
+}
+
+impl Board {
+    /// Estimates how good the position is for the player to move by playing `playouts` games with
+    /// uniformly random moves. Returns the fraction of the games won, counting ties as half a
+    /// win, so `1.0` means that every playout was won. Returns `0.5` if `playouts` is `0`.
+    ///
+    /// This is much cheaper than a search with the engine, but also much less accurate.
+    pub fn monte_carlo_eval(&self, playouts: u32, rng: &mut impl Rng) -> f32 {
+        if playouts == 0 {
+            return 0.5;
+        }
+        let player = self.player_to_move;
+        let mut score = 0.0;
+        let mut moves = MoveList::new();
+        for _ in 0..playouts {
+            let mut board = *self;
+            // A full board is always decided, so there is a legal move until the game is over.
+            let outcome = loop {
+                if let Some(outcome) = board.winner() {
+                    break outcome;
+                }
+                board.generate_moves_into(&mut moves);
+                let m = moves[rng.gen_range(0..moves.len())];
+                // SAFETY: m is a valid Move.
+                board = unsafe { board.advance_state_unsafe(m) };
+            };
+            score += outcome.score_for(player);
+        }
+        score / playouts as f32
+    }
+
+    /// Statically evaluates the position for `perspective` with the default [`EvalWeights`].
+    /// Positive scores are good for `perspective`. A won game scores [`EVAL_WIN`], a lost one
+    /// `-EVAL_WIN` and a tie `0`.
+    pub fn evaluate(&self, perspective: Player) -> i32 {
+        self.evaluate_with(perspective, &EvalWeights::default())
+    }
+
+    /// Same as [`evaluate`](Self::evaluate), but with custom weights, e.g. for tuning.
+    pub fn evaluate_with(&self, perspective: Player, weights: &EvalWeights) -> i32 {
+        let opponent = perspective.opponent();
+        match self.winner().map(Outcome::player) {
+            Some(None) => return 0,
+            Some(Some(winner)) if winner == perspective => return EVAL_WIN,
+            Some(Some(_)) => return -EVAL_WIN,
+            None => {}
+        }
+
+        let score = self.player_score(perspective, weights) - self.player_score(opponent, weights);
+        let free_move = match self.constraint() {
+            NextSubBoard::Any if self.player_to_move == perspective => weights.free_move,
+            NextSubBoard::Any => -weights.free_move,
+            NextSubBoard::Sub(_) => 0,
+        };
+        score + free_move
+    }
+
+    /// Returns the score of the features of `player` in [`evaluate_with`](Self::evaluate_with).
+    fn player_score(&self, player: Player, weights: &EvalWeights) -> i32 {
+        let decided = self.sub_wins.x.0 | self.sub_wins.o.0 | self.sub_wins.tie.0;
+        let won = self.sub_wins[player];
+        let mut score = won.0.count_ones() as i32 * weights.sub_board
+            + (won.0 & CENTER).count_ones() as i32 * weights.center_sub_board
+            + (won.0 & CORNERS).count_ones() as i32 * weights.corner_sub_board
+            + won.open_lines(BitBoard(decided & !won.0)).1 as i32 * weights.two_in_a_row;
+
+        for (major, sub_board) in self.board.iter().enumerate() {
+            if decided & 1 << major != 0 {
+                continue;
+            }
+            let own = sub_board[player];
+            let opponent = BitBoard((sub_board.x.0 | sub_board.o.0) & !own.0);
+            score += (own.0 & CENTER).count_ones() as i32 * weights.center_cell
+                + (own.0 & CORNERS).count_ones() as i32 * weights.corner_cell
+                + own.open_lines(opponent).1 as i32 * weights.sub_board_two_in_a_row;
+        }
+        score
+    }
+}
@@ -0,0 +1,122 @@
+//! Games with a history of moves that can be navigated.
+
+use crate::{Board, Move, MoveError, Player};
+
+/// A move of a [`Game`] and the position it led to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GameMove {
+    /// The player who made the move.
+    pub player: Player,
+    pub m: Move,
+    /// The position after the move.
+    pub board: Board,
+}
+
+/// A game that records every move, so that moves can be undone and redone.
+///
+/// The game keeps undone moves until a different move is played, so it can also be used to step
+/// back and forth through a finished game with [`seek`](Self::seek).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Game {
+    start: Board,
+    history: Vec<GameMove>,
+    /// Number of moves of `history` that are currently played.
+    ply: usize,
+}
+
+impl Game {
+    /// Create a new [`Game`] starting from the given position.
+    pub fn new(start: Board) -> Self {
+        Self {
+            start,
+            history: Vec::new(),
+            ply: 0,
+        }
+    }
+
+    /// Returns the position the game started from.
+    pub fn start(&self) -> Board {
+        self.start
+    }
+
+    /// Returns the current position.
+    pub fn board(&self) -> Board {
+        match self.ply {
+            0 => self.start,
+            ply => self.history[ply - 1].board,
+        }
+    }
+
+    /// Returns the number of moves played to reach the current position.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// Returns the moves played to reach the current position.
+    pub fn moves(&self) -> &[GameMove] {
+        &self.history[..self.ply]
+    }
+
+    /// Returns all recorded moves, including those that were undone and can be redone.
+    pub fn history(&self) -> &[GameMove] {
+        &self.history
+    }
+
+    /// Plays a move in the current position. If the move is the next move that can be redone,
+    /// this is the same as [`redo`](Self::redo). Otherwise, the moves that could be redone are
+    /// discarded.
+    pub fn play(&mut self, m: Move) -> Result<(), MoveError> {
+        let board = self.board();
+        let next = board.try_advance(m)?;
+        if self.history.get(self.ply).is_some_and(|next| next.m == m) {
+            self.ply += 1;
+            return Ok(());
+        }
+        self.history.truncate(self.ply);
+        self.history.push(GameMove {
+            player: board.player_to_move,
+            m,
+            board: next,
+        });
+        self.ply += 1;
+        Ok(())
+    }
+
+    /// Returns `true` if there is a move to undo.
+    pub fn can_undo(&self) -> bool {
+        self.ply > 0
+    }
+
+    /// Returns `true` if there is an undone move to redo.
+    pub fn can_redo(&self) -> bool {
+        self.ply < self.history.len()
+    }
+
+    /// Takes back the last move and returns it, or `None` if no move was played.
+    pub fn undo(&mut self) -> Option<Move> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.ply -= 1;
+        Some(self.history[self.ply].m)
+    }
+
+    /// Plays the last undone move again and returns it, or `None` if there is none.
+    pub fn redo(&mut self) -> Option<Move> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.ply += 1;
+        Some(self.history[self.ply - 1].m)
+    }
+
+    /// Goes to the position after `ply` moves of the [history](Self::history). Returns `false` and
+    /// stays at the current position if the history has fewer moves.
+    pub fn seek(&mut self, ply: usize) -> bool {
+        if ply > self.history.len() {
+            return false;
+        }
+        self.ply = ply;
+        true
+    }
+}
@@ -0,0 +1,30 @@
+//! Ultimate TicTacToe game state and rules.
+
+mod ascii;
+mod builder;
+mod error;
+mod eval;
+mod game;
+mod notation;
+mod pack;
+mod perft;
+mod record;
+mod reference;
+mod render;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd;
+mod state;
+mod symmetry;
+mod validate;
+mod zobrist;
+
+pub use builder::*;
+pub use error::*;
+pub use eval::*;
+pub use game::*;
+pub use pack::*;
+pub use record::*;
+pub use reference::*;
+pub use render::*;
+pub use state::*;
+pub use symmetry::*;
@@ -0,0 +1,236 @@
+//! Compact text notation for positions, similar to FEN in chess.
+//!
+//! The notation has three fields separated by spaces:
+//!
+//! 1. The cells of the sub-boards in order, separated by `/`. The cells of a sub-board are written
+//!    in order as `X` and `O`, with runs of empty cells written as a digit from `1` to `9`.
+//! 2. The player to move, `X` or `O`.
+//! 3. The sub-board the player has to move in, from `0` to `8`, or `-` if they can move anywhere.
+//!
+//! For example, the position after X plays in the center cell of the center sub-board is
+//! `9/9/9/9/4X4/9/9/9/9 O 4`. The decided sub-boards follow from the cells, and the [`Rules`] of
+//! the board are not part of the notation.
+//!
+//! [`Rules`]: crate::Rules
+
+use std::fmt::Write as _;
+
+use crate::{Board, NextSubBoard, NotationError, Player};
+
+impl Board {
+    /// Returns the position in the notation described in the [module documentation](self).
+    pub fn to_notation(&self) -> String {
+        let mut s = String::new();
+        for (major, sub_board) in self.board.iter().enumerate() {
+            if major > 0 {
+                s.push('/');
+            }
+            let mut empty = 0;
+            for minor in 0..9 {
+                let cell = if sub_board.x.0 & 1 << minor != 0 {
+                    'X'
+                } else if sub_board.o.0 & 1 << minor != 0 {
+                    'O'
+                } else {
+                    empty += 1;
+                    continue;
+                };
+                if empty > 0 {
+                    write!(s, "{}", empty).unwrap();
+                    empty = 0;
+                }
+                s.push(cell);
+            }
+            if empty > 0 {
+                write!(s, "{}", empty).unwrap();
+            }
+        }
+        let player = match self.player_to_move {
+            Player::X => 'X',
+            Player::O => 'O',
+        };
+        match self.constraint() {
+            NextSubBoard::Any => write!(s, " {} -", player).unwrap(),
+            NextSubBoard::Sub(major) => write!(s, " {} {}", player, major).unwrap(),
+        }
+        s
+    }
+}
+
+impl Board {
+    /// Parses a position in the notation described in the [module documentation](self). The
+    /// returned board uses the default [`Rules`](crate::Rules).
+    ///
+    /// Besides the syntax, this checks that the position is consistent with
+    /// [`validate`](Self::validate).
+    pub fn from_notation(s: &str) -> Result<Self, NotationError> {
+        let invalid = |msg: &str| NotationError::Invalid(msg.to_string());
+        let fields: Vec<_> = s.split_whitespace().collect();
+        let [cells, player, next_sub_board] = fields[..] else {
+            return Err(invalid("expected 3 fields separated by spaces"));
+        };
+
+        let mut board = Board::new();
+        let sub_boards: Vec<_> = cells.split('/').collect();
+        if sub_boards.len() != 9 {
+            return Err(invalid("expected 9 sub-boards separated by `/`"));
+        }
+        for (major, cells) in sub_boards.into_iter().enumerate() {
+            let sub_board = &mut board.board[major];
+            let mut minor = 0;
+            for c in cells.chars() {
+                match c {
+                    'X' | 'O' => {
+                        let player = if c == 'X' { Player::X } else { Player::O };
+                        // Extra cells are reported after the loop.
+                        if minor < 9 {
+                            sub_board[player].0 |= 1 << minor;
+                        }
+                        minor += 1;
+                    }
+                    '1'..='9' => minor += c as u32 - '0' as u32,
+                    _ => return Err(invalid(&format!("invalid cell `{}`", c))),
+                }
+            }
+            if minor != 9 {
+                return Err(invalid(&format!(
+                    "sub-board {} has {} cells instead of 9",
+                    major, minor
+                )));
+            }
+        }
+
+        board.player_to_move = parse_player(player)?;
+        board.set_constraint(parse_constraint(next_sub_board)?);
+        board.update_derived_state();
+        board.validate()?;
+        Ok(board)
+    }
+}
+
+/// Parses the player to move field of the notation.
+pub(crate) fn parse_player(s: &str) -> Result<Player, NotationError> {
+    match s {
+        "X" => Ok(Player::X),
+        "O" => Ok(Player::O),
+        _ => Err(NotationError::Invalid(
+            "player to move must be `X` or `O`".to_string(),
+        )),
+    }
+}
+
+/// Parses the next sub-board field of the notation.
+pub(crate) fn parse_constraint(s: &str) -> Result<NextSubBoard, NotationError> {
+    match s {
+        "-" => Ok(NextSubBoard::Any),
+        major => match major.parse::<u8>() {
+            Ok(major) if major < 9 => Ok(NextSubBoard::Sub(major)),
+            _ => Err(NotationError::Invalid(
+                "next sub-board must be `-` or from `0` to `8`".to_string(),
+            )),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{Move, PositionError};
+
+    /// The example of the module documentation.
+    const CENTER: &str = "9/9/9/9/4X4/9/9/9/9 O 4";
+
+    #[test]
+    fn write_example() {
+        let board = Board::new().advance_state(Move::new(4, 4)).unwrap();
+        assert_eq!(board.to_notation(), CENTER);
+        assert_eq!(Board::new().to_notation(), "9/9/9/9/9/9/9/9/9 X -");
+    }
+
+    #[test]
+    fn round_trip_random_games() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..50 {
+            let mut board = Board::new();
+            loop {
+                let notation = board.to_notation();
+                assert_eq!(Board::from_notation(&notation), Ok(board), "{}", notation);
+                let Some(&m) = board.generate_moves().choose(&mut rng) else {
+                    break;
+                };
+                board = board.advance_state(m).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn parse_example() {
+        let board = Board::from_notation(CENTER).unwrap();
+        assert_eq!(board, Board::new().advance_state(Move::new(4, 4)).unwrap());
+    }
+
+    #[test]
+    fn parse_invalid_syntax() {
+        for s in [
+            "9/9/9/9/4X4/9/9/9/9 O",
+            "9/9/9/9/4X4/9/9/9/9 O 4 4",
+            "9/9/9/9/4X4/9/9/9 O 4",
+            "9/9/9/9/4X4/9/9/9/9/9 O 4",
+            "9/9/9/9/4Y4/9/9/9/9 O 4",
+            "9/9/9/9/4X3/9/9/9/9 O 4",
+            "9/9/9/9/4X5/9/9/9/9 O 4",
+            "9/9/9/9/4X4/9/9/9/9 Z 4",
+            "9/9/9/9/4X4/9/9/9/9 O 9",
+            "9/9/9/9/4X4/9/9/9/9 O x",
+        ] {
+            assert!(
+                matches!(Board::from_notation(s), Err(NotationError::Invalid(_))),
+                "{} was accepted",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn parse_inconsistent_position() {
+        // Cells occupied by both players, a stale sub_wins and a stale move count cannot be
+        // written in the notation, since sub_wins and the move count are recomputed.
+        for (s, error) in [
+            (
+                "XXXOOO3/9/9/9/9/9/9/9/9 X -",
+                PositionError::BothPlayersWon { major: 0 },
+            ),
+            (
+                "9/9/9/9/4X4/9/9/9/9 X 4",
+                PositionError::WrongPlayerToMove {
+                    x: 1,
+                    o: 0,
+                    player: Player::X,
+                },
+            ),
+            (
+                "XXX6/OO7/9/9/9/9/9/9/9 O 0",
+                PositionError::NextSubBoardDecided { major: 0 },
+            ),
+            (
+                "9/9/9/9/4X4/9/9/9/9 O 3",
+                PositionError::NextSubBoardUnreachable { major: 3 },
+            ),
+            (
+                "9/9/9/9/4X4/9/9/9/9 O -",
+                PositionError::AnywhereUnreachable,
+            ),
+        ] {
+            assert_eq!(
+                Board::from_notation(s),
+                Err(NotationError::Position(error)),
+                "{}",
+                s
+            );
+        }
+    }
+}
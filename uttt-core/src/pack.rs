@@ -0,0 +1,169 @@
+//! Fixed-size binary encoding of positions for databases and network transfer.
+//!
+//! Layout of version 1, [`PACKED_BOARD_LEN`] bytes:
+//!
+//! - Byte 0: the version, `1`.
+//! - Byte 1: bit 0 is set if O is to move, bit 1 is set if [`Rules::strict_game_over`] is
+//!   enabled and bits 4 to 7 hold the next sub-board (`9` if any). Bits 2 and 3 are zero.
+//! - Bytes 2 to 18: a little-endian bit stream of the 9 sub-boards in order, 15 bits each. A
+//!   sub-board is stored as the number `sum(cell * 3^minor)`, where an empty cell is `0`, X is `1`
+//!   and O is `2`. The last bit is zero.
+//!
+//! The decided sub-boards follow from the cells and are not stored.
+//!
+//! [`Rules::strict_game_over`]: crate::Rules::strict_game_over
+
+use crate::{Board, NextSubBoard, Player, Rules, UnpackError};
+
+/// Length of a packed [`Board`] in bytes.
+pub const PACKED_BOARD_LEN: usize = 19;
+/// Version of the layout written by [`Board::pack`].
+pub const PACKED_BOARD_VERSION: u8 = 1;
+
+/// Number of bits of a packed sub-board.
+const SUB_BOARD_BITS: usize = 15;
+/// Number of possible sub-boards, `3^9`.
+const SUB_BOARD_VALUES: u32 = 19683;
+
+impl Board {
+    /// Encodes the position in the binary layout described in the [module documentation](self).
+    pub fn pack(&self) -> [u8; PACKED_BOARD_LEN] {
+        let mut bytes = [0; PACKED_BOARD_LEN];
+        bytes[0] = PACKED_BOARD_VERSION;
+        bytes[1] = (self.player_to_move == Player::O) as u8
+            | (self.rules.strict_game_over as u8) << 1
+            | u8::from(self.constraint()) << 4;
+
+        let stream = &mut bytes[2..];
+        for (major, sub_board) in self.board.iter().enumerate() {
+            let value = (0..9).rev().fold(0u32, |value, minor| {
+                let cell = if sub_board.x.0 & 1 << minor != 0 {
+                    1
+                } else if sub_board.o.0 & 1 << minor != 0 {
+                    2
+                } else {
+                    0
+                };
+                value * 3 + cell
+            });
+            for bit in 0..SUB_BOARD_BITS {
+                let index = major * SUB_BOARD_BITS + bit;
+                stream[index / 8] |= ((value >> bit & 1) as u8) << (index % 8);
+            }
+        }
+        bytes
+    }
+
+    /// Decodes a position encoded by [`pack`](Self::pack). The position is checked with
+    /// [`validate`](Self::validate).
+    pub fn unpack(bytes: &[u8; PACKED_BOARD_LEN]) -> Result<Self, UnpackError> {
+        if bytes[0] != PACKED_BOARD_VERSION {
+            return Err(UnpackError::UnsupportedVersion { version: bytes[0] });
+        }
+        let flags = bytes[1];
+        let constraint =
+            NextSubBoard::try_from(flags >> 4).map_err(|_| UnpackError::InvalidEncoding)?;
+        if flags & 0b1100 != 0 {
+            return Err(UnpackError::InvalidEncoding);
+        }
+
+        let mut board = Board::with_rules(Rules {
+            strict_game_over: flags & 0b10 != 0,
+        });
+        board.player_to_move = if flags & 1 != 0 { Player::O } else { Player::X };
+        board.set_constraint(constraint);
+
+        let stream = &bytes[2..];
+        if stream[PACKED_BOARD_LEN - 3] & 0x80 != 0 {
+            return Err(UnpackError::InvalidEncoding);
+        }
+        for (major, sub_board) in board.board.iter_mut().enumerate() {
+            let mut value = (0..SUB_BOARD_BITS).fold(0, |value, bit| {
+                let index = major * SUB_BOARD_BITS + bit;
+                value | ((stream[index / 8] >> (index % 8) & 1) as u32) << bit
+            });
+            if value >= SUB_BOARD_VALUES {
+                return Err(UnpackError::InvalidEncoding);
+            }
+            for minor in 0..9 {
+                match value % 3 {
+                    1 => sub_board.x.0 |= 1 << minor,
+                    2 => sub_board.o.0 |= 1 << minor,
+                    _ => {}
+                }
+                value /= 3;
+            }
+        }
+        board.update_derived_state();
+        board.validate()?;
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn round_trip_random_games() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for game in 0..50 {
+            let mut board = Board::with_rules(Rules {
+                strict_game_over: game % 2 == 0,
+            });
+            loop {
+                assert_eq!(Board::unpack(&board.pack()), Ok(board));
+                let Some(&m) = board.generate_moves().choose(&mut rng) else {
+                    break;
+                };
+                board = board.advance_state(m).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn unpack_rejects_invalid_encodings() {
+        let packed = Board::new().pack();
+        let unpack = |change: fn(&mut [u8; PACKED_BOARD_LEN])| {
+            let mut bytes = packed;
+            change(&mut bytes);
+            Board::unpack(&bytes)
+        };
+
+        assert_eq!(
+            unpack(|bytes| bytes[0] = 2),
+            Err(UnpackError::UnsupportedVersion { version: 2 })
+        );
+        // Bits 2 and 3 of the flags.
+        assert_eq!(
+            unpack(|bytes| bytes[1] |= 0b100),
+            Err(UnpackError::InvalidEncoding)
+        );
+        assert_eq!(
+            unpack(|bytes| bytes[1] |= 0b1000),
+            Err(UnpackError::InvalidEncoding)
+        );
+        // Next sub-board 10.
+        assert_eq!(
+            unpack(|bytes| bytes[1] = 10 << 4),
+            Err(UnpackError::InvalidEncoding)
+        );
+        // The last bit of the stream.
+        assert_eq!(
+            unpack(|bytes| bytes[PACKED_BOARD_LEN - 1] |= 0x80),
+            Err(UnpackError::InvalidEncoding)
+        );
+        // The first sub-board holds 2^15 - 1, which is not below 3^9.
+        assert_eq!(
+            unpack(|bytes| {
+                bytes[2] = 0xff;
+                bytes[3] |= 0x7f;
+            }),
+            Err(UnpackError::InvalidEncoding)
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Move path enumeration (perft), used for validating move generation.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::Board;
+
+impl Board {
+    /// Counts the number of positions reachable from this position in exactly `depth` moves.
+    /// Games that are decided before reaching `depth` do not contribute to the count.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        if self.winner().is_some() {
+            return 0;
+        }
+
+        let moves = self.move_list();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves
+            .iter()
+            // SAFETY: m is a valid Move.
+            .map(|m| unsafe { self.advance_state_unsafe(*m) }.perft(depth - 1))
+            .sum()
+    }
+
+    /// Same as [`perft`](Self::perft) but the moves from this position are split across `threads`
+    /// threads. If `threads` is `0`, the available parallelism of the machine is used.
+    pub fn perft_parallel(&self, depth: u32, threads: usize) -> u64 {
+        if depth <= 1 || self.winner().is_some() {
+            return self.perft(depth);
+        }
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+
+        let moves = self.generate_moves();
+        // Threads take the next move that has not been counted yet, so that a thread that got a
+        // cheap subtree does not sit idle.
+        let next = AtomicUsize::new(0);
+        thread::scope(|s| {
+            let workers: Vec<_> = (0..threads.min(moves.len()))
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut nodes = 0;
+                        while let Some(m) = moves.get(next.fetch_add(1, Ordering::Relaxed)) {
+                            // SAFETY: m is a valid Move.
+                            nodes += unsafe { self.advance_state_unsafe(*m) }.perft(depth - 1);
+                        }
+                        nodes
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("perft worker panicked"))
+                .sum()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Move;
+
+    /// Number of positions after 1 to 4 moves from the initial position.
+    const INITIAL_PERFT: [u64; 4] = [81, 720, 6336, 55080];
+
+    #[test]
+    fn perft_initial_position() {
+        let board = Board::new();
+        assert_eq!(board.perft(0), 1);
+        for (depth, &count) in (1..).zip(&INITIAL_PERFT) {
+            assert_eq!(board.perft(depth), count, "perft({})", depth);
+        }
+    }
+
+    #[test]
+    fn perft_parallel_matches_perft() {
+        let board = Board::new();
+        // 0 uses the available parallelism and 100 is more threads than moves.
+        for threads in [0, 1, 3, 100] {
+            for (depth, &count) in (1..).zip(&INITIAL_PERFT) {
+                assert_eq!(
+                    board.perft_parallel(depth, threads),
+                    count,
+                    "perft_parallel({}, {})",
+                    depth,
+                    threads
+                );
+            }
+        }
+        let board = board.advance_state(Move::new(4, 4)).unwrap();
+        assert_eq!(board.perft_parallel(3, 4), board.perft(3));
+    }
+}
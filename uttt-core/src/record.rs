@@ -0,0 +1,275 @@
+//! Records of whole games, with importers and exporters for formats used by online sites and bot
+//! arenas.
+//!
+//! Most sites number the cells of the whole 9x9 grid by row and column instead of using the
+//! (major, minor) pair of [`Move`]. Supported formats:
+//!
+//! - Row/column lists: one move per line (or separated by `;`), written as the row and column
+//!   separated by whitespace or a comma, e.g. `4 4`. This is the format bot arenas such as
+//!   CodinGame use. Rows and columns can be numbered from 0 or from 1, see [`Indexing`].
+//! - JSON dumps: an array of `[row, column]` pairs numbered from 0, either on its own or in the
+//!   `moves` field of an object, e.g. `{"moves": [[4, 4], [3, 5]]}`. Other fields are ignored.
+
+use std::fmt::Write as _;
+
+use crate::{Board, ImportError, Move, Outcome};
+
+/// How rows and columns are numbered in a row/column list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Indexing {
+    /// Rows and columns are numbered `0..9`.
+    #[default]
+    ZeroBased,
+    /// Rows and columns are numbered `1..=9`.
+    OneBased,
+}
+
+/// The moves of a game played from the initial position.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct GameRecord {
+    pub moves: Vec<Move>,
+}
+
+impl GameRecord {
+    /// Creates a record from a list of moves, checking that every move is legal.
+    pub fn from_moves(moves: Vec<Move>) -> Result<Self, ImportError> {
+        let record = Self { moves };
+        record.final_position()?;
+        Ok(record)
+    }
+
+    /// Replays the game and returns the final position.
+    pub fn final_position(&self) -> Result<Board, ImportError> {
+        Ok(Board::from_moves(self.moves.iter().copied())?)
+    }
+
+    /// Returns the outcome of the game, or `None` if it is unfinished or contains illegal moves.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.final_position().ok()?.winner()
+    }
+
+    /// Imports a row/column list. See the [module documentation](self).
+    pub fn from_row_col(s: &str, indexing: Indexing) -> Result<Self, ImportError> {
+        let offset = match indexing {
+            Indexing::ZeroBased => 0,
+            Indexing::OneBased => 1,
+        };
+        let moves = s
+            .split(['\n', ';'])
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let invalid = || ImportError::InvalidFormat(format!("invalid move `{}`", line));
+                let fields: Vec<_> = line
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|field| !field.is_empty())
+                    .collect();
+                let [row, col] = fields[..] else {
+                    return Err(invalid());
+                };
+                let coordinate = |s: &str| {
+                    s.parse::<u32>()
+                        .ok()
+                        .and_then(|n| n.checked_sub(offset))
+                        .filter(|&n| n < 9)
+                        .ok_or_else(invalid)
+                };
+                Ok(Move::from_global(coordinate(row)?, coordinate(col)?))
+            })
+            .collect::<Result<_, _>>()?;
+        Self::from_moves(moves)
+    }
+
+    /// Exports the record as a row/column list with one move per line.
+    pub fn to_row_col(&self, indexing: Indexing) -> String {
+        let offset = match indexing {
+            Indexing::ZeroBased => 0,
+            Indexing::OneBased => 1,
+        };
+        let mut s = String::new();
+        for &m in &self.moves {
+            let (row, col) = m.global();
+            writeln!(s, "{} {}", row + offset, col + offset).unwrap();
+        }
+        s
+    }
+
+    /// Imports a JSON dump. See the [module documentation](self).
+    pub fn from_json(s: &str) -> Result<Self, ImportError> {
+        let invalid = |msg: &str| ImportError::InvalidFormat(msg.to_string());
+        let mut parser = JsonParser { s: s.as_bytes() };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if !parser.s.is_empty() {
+            return Err(invalid("trailing characters after JSON value"));
+        }
+
+        let moves = match value {
+            Json::Array(moves) => moves,
+            Json::Object(fields) => match fields.into_iter().find(|(key, _)| key == "moves") {
+                Some((_, Json::Array(moves))) => moves,
+                _ => return Err(invalid("expected a `moves` array")),
+            },
+            _ => return Err(invalid("expected an array or an object")),
+        };
+        let moves = moves
+            .into_iter()
+            .map(|m| match m.as_slice() {
+                Some(&[Json::Number(row), Json::Number(col)]) if row < 9 && col < 9 => {
+                    Ok(Move::from_global(row as u32, col as u32))
+                }
+                _ => Err(invalid("expected a [row, column] pair")),
+            })
+            .collect::<Result<_, _>>()?;
+        Self::from_moves(moves)
+    }
+
+    /// Exports the record as a JSON object with a `moves` array of `[row, column]` pairs.
+    pub fn to_json(&self) -> String {
+        let moves: Vec<_> = self
+            .moves
+            .iter()
+            .map(|&m| {
+                let (row, col) = m.global();
+                format!("[{},{}]", row, col)
+            })
+            .collect();
+        format!("{{\"moves\":[{}]}}", moves.join(","))
+    }
+}
+
+/// The subset of JSON needed for importing games. Numbers are limited to non-negative integers.
+enum Json {
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+    /// `true`, `false` or `null`.
+    Literal,
+}
+
+impl Json {
+    fn as_slice(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    s: &'a [u8],
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let [c, rest @ ..] = self.s {
+            if !c.is_ascii_whitespace() {
+                break;
+            }
+            self.s = rest;
+        }
+    }
+
+    /// Consumes `c` after optional whitespace, returning whether it was there.
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_whitespace();
+        match self.s {
+            [first, rest @ ..] if *first == c => {
+                self.s = rest;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, ImportError> {
+        let invalid = |msg: &str| ImportError::InvalidFormat(msg.to_string());
+        self.skip_whitespace();
+        match self.s.first() {
+            Some(b'[') => {
+                self.s = &self.s[1..];
+                let mut values = Vec::new();
+                if !self.eat(b']') {
+                    loop {
+                        values.push(self.value()?);
+                        if self.eat(b']') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return Err(invalid("expected `,` or `]`"));
+                        }
+                    }
+                }
+                Ok(Json::Array(values))
+            }
+            Some(b'{') => {
+                self.s = &self.s[1..];
+                let mut fields = Vec::new();
+                if !self.eat(b'}') {
+                    loop {
+                        self.skip_whitespace();
+                        let Json::String(key) = self.value()? else {
+                            return Err(invalid("expected a string key"));
+                        };
+                        if !self.eat(b':') {
+                            return Err(invalid("expected `:`"));
+                        }
+                        fields.push((key, self.value()?));
+                        if self.eat(b'}') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return Err(invalid("expected `,` or `}`"));
+                        }
+                    }
+                }
+                Ok(Json::Object(fields))
+            }
+            Some(b'"') => {
+                let mut string = Vec::new();
+                let mut rest = &self.s[1..];
+                loop {
+                    match rest {
+                        [b'"', tail @ ..] => {
+                            rest = tail;
+                            break;
+                        }
+                        [b'\\', c, tail @ ..] => {
+                            string.push(*c);
+                            rest = tail;
+                        }
+                        [c, tail @ ..] => {
+                            string.push(*c);
+                            rest = tail;
+                        }
+                        [] => return Err(invalid("unterminated string")),
+                    }
+                }
+                self.s = rest;
+                String::from_utf8(string)
+                    .map(Json::String)
+                    .map_err(|_| invalid("invalid UTF-8 in string"))
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let len = self.s.iter().take_while(|c| c.is_ascii_digit()).count();
+                let (digits, rest) = self.s.split_at(len);
+                self.s = rest;
+                std::str::from_utf8(digits)
+                    .ok()
+                    .and_then(|digits| digits.parse().ok())
+                    .map(Json::Number)
+                    .ok_or_else(|| invalid("number out of range"))
+            }
+            _ => {
+                for literal in [&b"true"[..], b"false", b"null"] {
+                    if let Some(rest) = self.s.strip_prefix(literal) {
+                        self.s = rest;
+                        return Ok(Json::Literal);
+                    }
+                }
+                Err(invalid("unsupported JSON value"))
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! A slow but straightforward implementation of the game rules, used as a reference to check the
+//! optimized [`Board`](crate::Board) against.
+
+use crate::{Move, Outcome, Player};
+
+/// The rows, columns and diagonals of a 3x3 grid, as cell indices.
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+/// Returns the player owning a complete line in the grid, if any. X is checked first.
+fn line_owner(grid: [Option<Player>; 9]) -> Option<Player> {
+    [Player::X, Player::O].into_iter().find(|&player| {
+        LINES
+            .iter()
+            .any(|line| line.iter().all(|&i| grid[i] == Some(player)))
+    })
+}
+
+/// Reference implementation of the game rules. Every cell is stored separately and everything else
+/// is recomputed from the cells when needed, without any bit tricks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleBoard {
+    cells: [[Option<Player>; 9]; 9],
+    player_to_move: Player,
+    last_move: Option<Move>,
+}
+
+impl Default for SimpleBoard {
+    fn default() -> Self {
+        Self {
+            cells: [[None; 9]; 9],
+            player_to_move: Player::X,
+            last_move: None,
+        }
+    }
+}
+
+impl SimpleBoard {
+    /// Create a new empty [`SimpleBoard`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn player_to_move(&self) -> Player {
+        self.player_to_move
+    }
+
+    /// Returns the player occupying the cell, if any.
+    pub fn cell(&self, major: u32, minor: u32) -> Option<Player> {
+        self.cells[major as usize][minor as usize]
+    }
+
+    /// Returns the outcome of a sub-board or `None` if it is undecided. A sub-board is a tie if it
+    /// is full without a line.
+    pub fn sub_board_winner(&self, major: u32) -> Option<Outcome> {
+        let cells = self.cells[major as usize];
+        match line_owner(cells) {
+            Some(player) => Some(player.into()),
+            None if cells.iter().all(Option::is_some) => Some(Outcome::Tie),
+            None => None,
+        }
+    }
+
+    /// Returns the sub-board the next move has to be played in, or `None` if it can be played in
+    /// any undecided sub-board.
+    pub fn next_sub_board(&self) -> Option<u32> {
+        let major = self.last_move?.minor;
+        if self.sub_board_winner(major).is_none() {
+            Some(major)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the outcome of the whole game or `None` if it is still in progress. The game is a
+    /// tie if every sub-board is decided without a line of won sub-boards.
+    pub fn winner(&self) -> Option<Outcome> {
+        let mut grid = [None; 9];
+        for (major, owner) in grid.iter_mut().enumerate() {
+            *owner = self
+                .sub_board_winner(major as u32)
+                .and_then(Outcome::player);
+        }
+        match line_owner(grid) {
+            Some(player) => Some(player.into()),
+            None if (0..9).all(|major| self.sub_board_winner(major).is_some()) => {
+                Some(Outcome::Tie)
+            }
+            None => None,
+        }
+    }
+
+    /// Returns `true` if the move can be played in this position. No moves can be played once the
+    /// game is decided.
+    pub fn is_legal(&self, m: Move) -> bool {
+        if m.major > 8 || m.minor > 8 || self.winner().is_some() {
+            return false;
+        }
+        if self.next_sub_board().is_some_and(|major| major != m.major) {
+            return false;
+        }
+        self.sub_board_winner(m.major).is_none() && self.cell(m.major, m.minor).is_none()
+    }
+
+    /// Returns the legal moves, ordered by major and then minor index.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        (0..9)
+            .flat_map(|major| (0..9).map(move |minor| Move { major, minor }))
+            .filter(|&m| self.is_legal(m))
+            .collect()
+    }
+
+    /// Plays the move if it is legal. Returns `false` and leaves the board unchanged otherwise.
+    pub fn play(&mut self, m: Move) -> bool {
+        if !self.is_legal(m) {
+            return false;
+        }
+        self.cells[m.major as usize][m.minor as usize] = Some(self.player_to_move);
+        self.player_to_move = self.player_to_move.opponent();
+        self.last_move = Some(m);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{Board, NextSubBoard, Rules};
+
+    /// Returns the state of a sub-board according to `Board::sub_wins`.
+    fn sub_board_winner(board: &Board, major: u32) -> Option<Outcome> {
+        let mask = 1 << major;
+        if board.sub_wins.x.0 & mask != 0 {
+            Some(Outcome::X)
+        } else if board.sub_wins.o.0 & mask != 0 {
+            Some(Outcome::O)
+        } else if board.sub_wins.tie.0 & mask != 0 {
+            Some(Outcome::Tie)
+        } else {
+            None
+        }
+    }
+
+    fn assert_same(board: &Board, simple: &SimpleBoard) {
+        assert_eq!(board.player_to_move, simple.player_to_move());
+        for major in 0..9 {
+            for minor in 0..9 {
+                assert_eq!(
+                    board.cell(major, minor),
+                    simple.cell(major, minor),
+                    "cell ({major}, {minor}) differs"
+                );
+            }
+            assert_eq!(
+                sub_board_winner(board, major),
+                simple.sub_board_winner(major),
+                "sub-board {major} differs"
+            );
+        }
+        let constraint = match simple.next_sub_board() {
+            Some(major) => NextSubBoard::Sub(major as u8),
+            None => NextSubBoard::Any,
+        };
+        assert_eq!(board.constraint(), constraint);
+        assert_eq!(board.winner(), simple.winner());
+        assert!(
+            board.generate_moves() == simple.legal_moves(),
+            "legal moves differ"
+        );
+    }
+
+    #[test]
+    fn random_games_match_board() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..50 {
+            // The reference does not accept moves once the game is decided.
+            let mut board = Board::with_rules(Rules {
+                strict_game_over: true,
+            });
+            let mut simple = SimpleBoard::new();
+            assert_same(&board, &simple);
+            while let Some(&m) = simple.legal_moves().choose(&mut rng) {
+                // An illegal move is rejected by both.
+                let other = Move::new(rng.gen_range(0..9), rng.gen_range(0..9));
+                assert_eq!(
+                    board.advance_state(other).is_some(),
+                    simple.is_legal(other),
+                    "legality of {other:?} differs"
+                );
+
+                board = board.advance_state(m).expect("move is legal on Board");
+                assert!(simple.play(m));
+                assert_same(&board, &simple);
+            }
+            assert!(board.winner().is_some());
+        }
+    }
+}
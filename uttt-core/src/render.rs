@@ -0,0 +1,174 @@
+//! Rendering of positions for terminals.
+
+use std::fmt::Write as _;
+
+use crate::{Board, Indexing, Move, Player};
+
+const RED: &str = "\x1b[31m";
+const BLUE: &str = "\x1b[34m";
+const YELLOW: &str = "\x1b[33m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Options of [`Board::render`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Draw the grid with box-drawing characters instead of ASCII.
+    pub unicode: bool,
+    /// Label the rows and columns, numbered like the row/column lists of [`GameRecord`].
+    ///
+    /// [`GameRecord`]: crate::GameRecord
+    pub labels: Option<Indexing>,
+    /// Color X, O and the highlights with ANSI escape codes.
+    pub color: bool,
+    /// Mark the empty cells the player to move can play in.
+    pub highlight_next: bool,
+    /// Move to put in brackets, usually the [last move](Board::last_move) that was played.
+    pub last_move: Option<Move>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            unicode: true,
+            labels: Some(Indexing::ZeroBased),
+            color: false,
+            highlight_next: true,
+            last_move: None,
+        }
+    }
+}
+
+/// Characters used to draw the grid. The horizontal lines list the characters from left to right:
+/// corner, line, junction and corner.
+struct Borders {
+    top: [char; 4],
+    middle: [char; 4],
+    bottom: [char; 4],
+    vertical: char,
+    empty: char,
+    legal: char,
+}
+
+const UNICODE_BORDERS: Borders = Borders {
+    top: ['┌', '─', '┬', '┐'],
+    middle: ['├', '─', '┼', '┤'],
+    bottom: ['└', '─', '┴', '┘'],
+    vertical: '│',
+    empty: '·',
+    legal: '•',
+};
+
+const ASCII_BORDERS: Borders = Borders {
+    top: ['+', '-', '+', '+'],
+    middle: ['+', '-', '+', '+'],
+    bottom: ['+', '-', '+', '+'],
+    vertical: '|',
+    empty: '.',
+    legal: '*',
+};
+
+impl Board {
+    /// Draws the position as a 9x9 grid with the sub-boards separated by grid lines. Unlike the
+    /// [`Display`](std::fmt::Display) implementation, the output is meant for people following a
+    /// game in a terminal. Every line ends with a newline.
+    ///
+    /// # Example
+    /// ```
+    /// use uttt_core::{Board, Move, RenderOptions};
+    ///
+    /// let board = Board::new().advance_state(Move::new(4, 0)).unwrap();
+    /// let options = RenderOptions {
+    ///     last_move: Some(Move::new(4, 0)),
+    ///     ..Default::default()
+    /// };
+    /// print!("{}", board.render(options));
+    /// ```
+    pub fn render(&self, options: RenderOptions) -> String {
+        let borders = if options.unicode {
+            &UNICODE_BORDERS
+        } else {
+            &ASCII_BORDERS
+        };
+        let offset = match options.labels {
+            Some(Indexing::ZeroBased) => 0,
+            Some(Indexing::OneBased) => 1,
+            None => 0,
+        };
+        let margin = if options.labels.is_some() { "  " } else { "" };
+        let legal = if options.highlight_next {
+            self.legal_move_mask()
+        } else {
+            0
+        };
+
+        let mut out = String::new();
+        if options.labels.is_some() {
+            let mut line = format!("{margin} ");
+            for major_col in 0..3 {
+                for minor_col in 0..3 {
+                    write!(line, " {}", major_col * 3 + minor_col + offset).unwrap();
+                }
+                line.push_str("  ");
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+
+        let horizontal = |[left, line, junction, right]: [char; 4]| {
+            let segment: String = std::iter::repeat_n(line, 7).collect();
+            format!("{margin}{left}{segment}{junction}{segment}{junction}{segment}{right}\n")
+        };
+
+        out.push_str(&horizontal(borders.top));
+        for major_row in 0..3 {
+            for minor_row in 0..3 {
+                if options.labels.is_some() {
+                    write!(out, "{} ", major_row * 3 + minor_row + offset).unwrap();
+                }
+                out.push(borders.vertical);
+                for major_col in 0..3 {
+                    let major = major_row * 3 + major_col;
+                    // The separators before, between and after the 3 cells of the sub-board.
+                    let mut separators = [' '; 4];
+                    let mut glyphs = [String::new(), String::new(), String::new()];
+                    for (minor_col, glyph) in glyphs.iter_mut().enumerate() {
+                        let minor = minor_row * 3 + minor_col as u32;
+                        let (c, color) = match self.cell(major, minor) {
+                            Some(Player::X) => ('X', RED),
+                            Some(Player::O) => ('O', BLUE),
+                            None if legal & 1 << (major * 9 + minor) != 0 => {
+                                (borders.legal, YELLOW)
+                            }
+                            None => (borders.empty, ""),
+                        };
+                        let last = options.last_move == Some(Move::new(major, minor));
+                        if last {
+                            separators[minor_col] = '[';
+                            separators[minor_col + 1] = ']';
+                        }
+                        *glyph = if options.color && (last || !color.is_empty()) {
+                            let bold = if last { BOLD } else { "" };
+                            format!("{bold}{color}{c}{RESET}")
+                        } else {
+                            c.to_string()
+                        };
+                    }
+                    for (separator, glyph) in separators.iter().zip(&glyphs) {
+                        out.push(*separator);
+                        out.push_str(glyph);
+                    }
+                    out.push(separators[3]);
+                    out.push(borders.vertical);
+                }
+                out.push('\n');
+            }
+            out.push_str(&horizontal(if major_row == 2 {
+                borders.bottom
+            } else {
+                borders.middle
+            }));
+        }
+        out
+    }
+}
@@ -0,0 +1,101 @@
+//! Checks of all nine sub-boards at once with SIMD instructions, enabled by the `simd` feature on
+//! x86_64. Every x86_64 CPU supports SSE2, so no runtime detection is needed.
+
+use std::arch::x86_64::*;
+
+use crate::state::WIN_CONFIGURATIONS;
+use crate::{BitBoard, SubBoard, WinBoard};
+
+/// Computes the [`WinBoard`] of the sub-boards.
+pub(crate) fn sub_wins(board: &[SubBoard; 9]) -> WinBoard {
+    // SAFETY: SSE2 is part of the x86_64 baseline.
+    unsafe { sub_wins_sse2(board) }
+}
+
+/// The cells of X and O of the first 8 sub-boards fill one vector each, with one sub-board per
+/// 16-bit lane. The last sub-board takes the first two lanes of a third vector.
+#[target_feature(enable = "sse2")]
+fn sub_wins_sse2(board: &[SubBoard; 9]) -> WinBoard {
+    let x = _mm_setr_epi16(
+        board[0].x.0 as i16,
+        board[1].x.0 as i16,
+        board[2].x.0 as i16,
+        board[3].x.0 as i16,
+        board[4].x.0 as i16,
+        board[5].x.0 as i16,
+        board[6].x.0 as i16,
+        board[7].x.0 as i16,
+    );
+    let o = _mm_setr_epi16(
+        board[0].o.0 as i16,
+        board[1].o.0 as i16,
+        board[2].o.0 as i16,
+        board[3].o.0 as i16,
+        board[4].o.0 as i16,
+        board[5].o.0 as i16,
+        board[6].o.0 as i16,
+        board[7].o.0 as i16,
+    );
+    let last = _mm_setr_epi16(board[8].x.0 as i16, board[8].o.0 as i16, 0, 0, 0, 0, 0, 0);
+
+    // Sets every lane that contains three in a row to all ones.
+    let has_line = |cells: __m128i| {
+        WIN_CONFIGURATIONS
+            .into_iter()
+            .fold(_mm_setzero_si128(), |won, line| {
+                let line = _mm_set1_epi16(line as i16);
+                _mm_or_si128(won, _mm_cmpeq_epi16(_mm_and_si128(cells, line), line))
+            })
+    };
+    // Takes one bit of every lane, so that bit `i` is set if lane `i` is all ones.
+    let lanes = |v: __m128i| _mm_movemask_epi8(_mm_packs_epi16(v, _mm_setzero_si128())) as u16;
+
+    let last_won = lanes(has_line(last));
+    let x_won = lanes(has_line(x)) | (last_won & 1) << 8;
+    let o_won = lanes(has_line(o)) | (last_won >> 1 & 1) << 8;
+    let full = _mm_cmpeq_epi16(_mm_or_si128(x, o), _mm_set1_epi16(0b111111111));
+    let last_full = (board[8].x.0 | board[8].o.0 == 0b111111111) as u16;
+    let full = lanes(full) | last_full << 8;
+
+    WinBoard {
+        x: BitBoard(x_won),
+        o: BitBoard(o_won),
+        tie: BitBoard(full & !x_won & !o_won),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn matches_scalar_check() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10_000 {
+            let mut board = [SubBoard::default(); 9];
+            for sub_board in &mut board {
+                // Every cell is empty, X or O, so that full sub-boards occur.
+                for minor in 0..9 {
+                    match rng.gen_range(0..3) {
+                        1 => sub_board.x.0 |= 1 << minor,
+                        2 => sub_board.o.0 |= 1 << minor,
+                        _ => {}
+                    }
+                }
+            }
+
+            let mut expected = WinBoard::default();
+            for (major, sub_board) in board.iter().enumerate() {
+                let (x_won, o_won) = (sub_board.x.has_line(), sub_board.o.has_line());
+                let full = sub_board.x.0 | sub_board.o.0 == 0b111111111;
+                expected.x.0 |= (x_won as u16) << major;
+                expected.o.0 |= (o_won as u16) << major;
+                expected.tie.0 |= ((full && !x_won && !o_won) as u16) << major;
+            }
+            assert_eq!(sub_wins(&board), expected);
+        }
+    }
+}
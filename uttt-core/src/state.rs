@@ -0,0 +1,1328 @@
+//! Data structures for representing the state of the game.
+
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::{BitAnd, BitOr, Index, IndexMut};
+use std::str::FromStr;
+
+use crate::zobrist::ZOBRIST;
+use crate::{MoveError, MoveRejection, ParseError, PositionError, ReplayError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Player {
+    X,
+    O,
+}
+
+impl Player {
+    /// Returns the other player.
+    pub fn opponent(self) -> Self {
+        match self {
+            Player::X => Player::O,
+            Player::O => Player::X,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HasWinner {
+    Yes,
+    Tie,
+    InProgress,
+}
+
+/// Result of a decided game or sub-board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    X,
+    O,
+    Tie,
+}
+
+impl Outcome {
+    /// Returns the winner, or `None` for a tie.
+    pub fn player(self) -> Option<Player> {
+        match self {
+            Outcome::X => Some(Player::X),
+            Outcome::O => Some(Player::O),
+            Outcome::Tie => None,
+        }
+    }
+
+    /// Returns the score of the outcome for `player`: `1.0` for a win, `0.5` for a tie and `0.0`
+    /// for a loss.
+    pub fn score_for(self, player: Player) -> f32 {
+        match self.player() {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+}
+
+/// The win of `player`.
+impl From<Player> for Outcome {
+    fn from(player: Player) -> Self {
+        match player {
+            Player::X => Outcome::X,
+            Player::O => Outcome::O,
+        }
+    }
+}
+
+/// Optional rules that change which moves a [`Board`] accepts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rules {
+    /// Reject every move once the game is decided. When disabled (the default), moves can still be
+    /// played in undecided sub-boards after the game is decided, so callers have to check
+    /// [`Board::winner`] themselves.
+    pub strict_game_over: bool,
+}
+
+/// Representation of the Ultimate-TicTacToe game board.
+#[derive(Clone, Copy)]
+pub struct Board {
+    pub sub_wins: WinBoard,
+    pub board: [SubBoard; 9],
+    pub player_to_move: Player,
+    /// The index of the next sub-board, in the range of `0..9`, or `9` if the next player can
+    /// move anywhere. Read and written through [`Board::constraint`] and
+    /// [`Board::set_constraint`], which keep it in range.
+    pub(crate) next_sub_board: u8,
+    pub rules: Rules,
+    /// See [`Board::move_count`].
+    pub(crate) ply: u8,
+    /// See [`Board::zobrist_hash`].
+    pub(crate) zobrist: u64,
+    /// See [`Board::last_move`].
+    pub(crate) last_move: Option<PackedMove>,
+}
+
+/// Where the player to move can play, see [`Board::constraint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NextSubBoard {
+    /// Any undecided sub-board.
+    Any,
+    /// Only the sub-board with this index, in the range `0..9`.
+    Sub(u8),
+}
+
+impl From<NextSubBoard> for u8 {
+    /// Converts to a sub-board index, where `9` means anywhere. This is how positions are
+    /// serialized.
+    fn from(next: NextSubBoard) -> Self {
+        match next {
+            NextSubBoard::Any => 9,
+            NextSubBoard::Sub(major) => major,
+        }
+    }
+}
+
+impl TryFrom<u8> for NextSubBoard {
+    type Error = PositionError;
+
+    /// Converts from a sub-board index, where `9` means anywhere.
+    fn try_from(next_sub_board: u8) -> Result<Self, Self::Error> {
+        match next_sub_board {
+            0..=8 => Ok(Self::Sub(next_sub_board)),
+            9 => Ok(Self::Any),
+            _ => Err(PositionError::NextSubBoardOutOfRange { next_sub_board }),
+        }
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            sub_wins: WinBoard::default(),
+            board: [SubBoard::default(); 9],
+            // Player X always starts.
+            player_to_move: Player::X,
+            // Initially can move anywhere.
+            next_sub_board: 9,
+            rules: Rules::default(),
+            ply: 0,
+            zobrist: ZOBRIST.next_sub_board[9],
+            last_move: None,
+        }
+    }
+}
+
+impl Board {
+    /// Create a new [`Board`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new [`Board`] using the given [`Rules`].
+    pub fn with_rules(rules: Rules) -> Self {
+        Self {
+            rules,
+            ..Self::default()
+        }
+    }
+
+    /// Returns where the player to move can play.
+    pub fn constraint(&self) -> NextSubBoard {
+        match self.next_sub_board {
+            9 => NextSubBoard::Any,
+            major => NextSubBoard::Sub(major),
+        }
+    }
+
+    /// Sets where the player to move can play and updates the
+    /// [Zobrist hash](Self::zobrist_hash). Whether the player can actually play there is not
+    /// checked, see [`validate`](Self::validate).
+    ///
+    /// # Panics
+    /// This method panics if the sub-board index is greater than 8.
+    pub fn set_constraint(&mut self, constraint: NextSubBoard) {
+        if let NextSubBoard::Sub(major) = constraint {
+            assert!(major < 9, "major index {} is out of range", major);
+        }
+        let next_sub_board = u8::from(constraint);
+        self.zobrist ^= ZOBRIST.next_sub_board[self.next_sub_board as usize]
+            ^ ZOBRIST.next_sub_board[next_sub_board as usize];
+        self.next_sub_board = next_sub_board;
+    }
+
+    /// Replays the moves from the initial position and returns the final position, or the index
+    /// and reason of the first illegal move.
+    pub fn from_moves(moves: impl IntoIterator<Item = Move>) -> Result<Self, ReplayError> {
+        moves
+            .into_iter()
+            .enumerate()
+            .try_fold(Self::new(), |board, (index, m)| {
+                board
+                    .check_move(m)
+                    .map_err(|error| ReplayError { index, error })?;
+                // SAFETY: the move is in range.
+                Ok(unsafe { board.advance_state_unsafe(m) })
+            })
+    }
+
+    /// Returns `true` if no more moves can be played because the game is decided and
+    /// [`Rules::strict_game_over`] is enabled.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.rules.strict_game_over && self.winner().is_some()
+    }
+
+    /// Returns the [`Board`] with the applied [`Move`] onto it. This does not change the original
+    /// [`Board`]. This method also doesn't check if the move is valid in the context of the game
+    /// state.
+    ///
+    /// Switches the next player to move.
+    ///
+    /// # Safety
+    ///
+    /// - `m` must be a valid [`Move`], meaning that the `major` field and the `minor` field must be
+    ///   between `0` and `8` inclusive. Any value outside this range will cause undefined behavior.
+    #[must_use = "advanced_state_unsafe does not modify original Board"]
+    pub unsafe fn advance_state_unsafe(mut self, m: Move) -> Self {
+        self.apply_move_unchecked(m);
+        self
+    }
+
+    /// Plays the move in place without checking it, see
+    /// [`advance_state_unsafe`](Self::advance_state_unsafe).
+    ///
+    /// # Safety
+    ///
+    /// The indices of `m` must be in the range `0..9`.
+    #[inline(always)]
+    unsafe fn apply_move_unchecked(&mut self, m: Move) {
+        let player = self.player_to_move;
+        // SAFETY: range is guaranteed to be valid by the caller. `board` is of length 9 and m.major
+        // is in range 0..9.
+        let sub_board = self.board.get_unchecked_mut(m.major as usize);
+        sub_board[player] = sub_board[player].advance_bitfield_state(m.minor);
+
+        // Update `sub_wins` to keep state in sync.
+        // Since we know the major position of the move, we only need to recompute the win state
+        // for one of the sub-boards. We also know the player so we only need to re-compute the
+        // bitfield of the player. A full sub-board is only a tie if the move did not win it.
+        let won = (sub_board[player].has_winner() == HasWinner::Yes) as u16;
+        let full = ((sub_board.x.0 | sub_board.o.0) == 0b111111111) as u16;
+        self.sub_wins[player].0 |= won << m.major;
+        self.sub_wins.tie.0 |= (full & !won) << m.major;
+
+        // Update `next_sub_board` for next turn.
+        // The next sub-board index is the same as the minor index for this turn, unless that
+        // sub-board has already been decided in which case the next player can move anywhere
+        // (`9`).
+        let sub_wins_or = self.sub_wins.x.0 | self.sub_wins.o.0 | self.sub_wins.tie.0;
+        let decided = (sub_wins_or >> m.minor) as u8 & 1;
+        let previous_next_sub_board = self.next_sub_board;
+        self.next_sub_board = m.minor as u8 + decided * (9 - m.minor as u8);
+
+        self.player_to_move = player.opponent();
+
+        self.ply += 1;
+        self.last_move = Some(PackedMove((m.major * 9 + m.minor) as u8));
+        self.zobrist ^= ZOBRIST.cells[player as usize][(m.major * 9 + m.minor) as usize]
+            ^ ZOBRIST.o_to_move
+            ^ ZOBRIST.next_sub_board[previous_next_sub_board as usize]
+            ^ ZOBRIST.next_sub_board[self.next_sub_board as usize];
+
+        #[cfg(any(debug_assertions, feature = "strict-checks"))]
+        self.assert_invariants();
+    }
+
+    /// Plays the move in place and returns what [`unmake_move`](Self::unmake_move) needs to take
+    /// it back. This avoids copying the board in searches that play and take back moves, such as
+    /// alpha-beta.
+    ///
+    /// Like [`advance_state_unsafe`](Self::advance_state_unsafe), this does not check that the
+    /// move is legal, so it should only be called with moves from [`moves`](Self::moves) or
+    /// [`generate_moves`](Self::generate_moves).
+    ///
+    /// # Panics
+    /// This method panics if the major or minor index of the move is greater than 8.
+    pub fn make_move_mut(&mut self, m: Move) -> UndoInfo {
+        assert!(m.major <= 8 && m.minor <= 8, "move is out of range");
+        let undo = UndoInfo {
+            m,
+            sub_wins: self.sub_wins,
+            next_sub_board: self.next_sub_board,
+            zobrist: self.zobrist,
+            last_move: self.last_move,
+        };
+        // SAFETY: the move is in range.
+        unsafe { self.apply_move_unchecked(m) };
+        undo
+    }
+
+    /// Takes back the move made with [`make_move_mut`](Self::make_move_mut) that returned `undo`.
+    /// Moves have to be taken back in the reverse order they were made.
+    pub fn unmake_move(&mut self, undo: UndoInfo) {
+        let player = self.player_to_move.opponent();
+        self.board[undo.m.major as usize][player].0 &= !(1 << undo.m.minor);
+        self.sub_wins = undo.sub_wins;
+        self.next_sub_board = undo.next_sub_board;
+        self.player_to_move = player;
+        self.ply -= 1;
+        self.zobrist = undo.zobrist;
+        self.last_move = undo.last_move;
+
+        #[cfg(any(debug_assertions, feature = "strict-checks"))]
+        self.assert_invariants();
+    }
+
+    /// Panics if the internal invariants of the board do not hold:
+    ///
+    /// - No cell is occupied by both X and O, and no bits outside of the 9 cells are set.
+    /// - `sub_wins` matches the state of the sub-boards.
+    /// - `next_sub_board` is `9` or an undecided sub-board.
+    /// - The move count matches the number of occupied cells.
+    /// - The Zobrist hash matches the position.
+    ///
+    /// This is checked after every move in debug builds or with the `strict-checks` feature.
+    #[cfg(any(debug_assertions, feature = "strict-checks"))]
+    fn assert_invariants(&self) {
+        const FULL: u16 = 0b111111111;
+
+        for (major, sub_board) in self.board.iter().enumerate() {
+            let (x, o) = (sub_board.x.0, sub_board.o.0);
+            assert_eq!(x & o, 0, "sub-board {} has overlapping X and O", major);
+            assert_eq!(
+                (x | o) & !FULL,
+                0,
+                "sub-board {} has invalid bits set",
+                major
+            );
+
+            let mask = 1 << major;
+            let x_won = sub_board.x.has_line();
+            let o_won = sub_board.o.has_line();
+            let tie = x | o == FULL && !x_won && !o_won;
+            assert!(
+                !(x_won && o_won),
+                "sub-board {} is won by both X and O",
+                major
+            );
+            assert_eq!(
+                self.sub_wins.x.0 & mask != 0,
+                x_won,
+                "sub_wins.x is out of sync for sub-board {}",
+                major
+            );
+            assert_eq!(
+                self.sub_wins.o.0 & mask != 0,
+                o_won,
+                "sub_wins.o is out of sync for sub-board {}",
+                major
+            );
+            assert_eq!(
+                self.sub_wins.tie.0 & mask != 0,
+                tie,
+                "sub_wins.tie is out of sync for sub-board {}",
+                major
+            );
+        }
+
+        assert!(
+            self.next_sub_board <= 9,
+            "invalid next_sub_board {}",
+            self.next_sub_board
+        );
+        if self.next_sub_board != 9 {
+            let decided = self.sub_wins.x.0 | self.sub_wins.o.0 | self.sub_wins.tie.0;
+            assert_eq!(
+                decided & 1 << self.next_sub_board,
+                0,
+                "next_sub_board {} is already decided",
+                self.next_sub_board
+            );
+        }
+        let stones: u32 = self
+            .board
+            .iter()
+            .map(|sub_board| (sub_board.x.0 | sub_board.o.0).count_ones())
+            .sum();
+        assert_eq!(self.ply as u32, stones, "move count is out of sync");
+        assert_eq!(
+            self.zobrist,
+            self.compute_zobrist_hash(),
+            "zobrist hash is out of sync"
+        );
+    }
+
+    /// Returns `true` if the [`Move`] can be played in this position. Use
+    /// [`check_move`](Self::check_move) to find out why a move cannot be played.
+    pub fn is_legal(&self, m: Move) -> bool {
+        self.check_move(m).is_ok()
+    }
+
+    /// Checks that the [`Move`] can be played in this position and returns the reason if not.
+    pub fn check_move(&self, m: Move) -> Result<(), MoveError> {
+        let Move { major, minor } = m;
+        // First, check that Move major and minor indexes are in range 0..9.
+        if major > 8 || minor > 8 {
+            return Err(MoveError::OutOfRange { major, minor });
+        }
+        if self.is_closed() {
+            return Err(MoveError::GameOver);
+        }
+        // Check that cell is open.
+        let sub_board = self.board[major as usize];
+        let mask = 1 << minor;
+        if sub_board.x.0 & mask != 0 || sub_board.o.0 & mask != 0 {
+            return Err(MoveError::CellOccupied { major, minor });
+        }
+        // Check that the sub-board is the one the player is supposed to move in.
+        if let NextSubBoard::Sub(expected) = self.constraint() {
+            if expected as u32 != major {
+                return Err(MoveError::WrongSubBoard {
+                    expected: expected as u32,
+                    major,
+                });
+            }
+        }
+        // Check that the sub-board has not already been won.
+        let mask = 1 << major;
+        if self.sub_wins.x.0 & mask != 0 || self.sub_wins.o.0 & mask != 0 {
+            return Err(MoveError::SubBoardDecided { major });
+        }
+        Ok(())
+    }
+
+    /// Returns the [`Board`] with the applied [`Move`] onto it or `None` if the move is invalid.
+    /// This does not change the original [`Board`]. Use [`check_move`](Self::check_move) to find
+    /// out why a move is invalid.
+    ///
+    /// Switches the next player to move.
+    ///
+    /// For performance critical code, prefer [`advance_state_unsafe`] instead.
+    pub fn advance_state(self, m: Move) -> Option<Self> {
+        self.check_move(m).ok()?;
+        // SAFETY: the move is in range.
+        Some(unsafe { self.advance_state_unsafe(m) })
+    }
+
+    /// Same as [`advance_state`](Self::advance_state) but returns the [`MoveError`] describing why
+    /// the move was rejected instead of `None`, e.g. to tell users why their move is illegal.
+    pub fn try_advance(self, m: Move) -> Result<Self, MoveError> {
+        self.check_move(m)?;
+        // SAFETY: the move is in range.
+        Ok(unsafe { self.advance_state_unsafe(m) })
+    }
+
+    /// Same as [`advance_state`](Self::advance_state) but returns a [`MoveRejection`] describing
+    /// the rejected move instead of `None`. This is meant for servers that need to log why moves
+    /// received from clients are rejected.
+    pub fn advance_state_with_report(self, m: Move) -> Result<Self, MoveRejection> {
+        match self.check_move(m) {
+            // SAFETY: the move is in range.
+            Ok(()) => Ok(unsafe { self.advance_state_unsafe(m) }),
+            Err(error) => Err(MoveRejection {
+                error,
+                major: m.major,
+                minor: m.minor,
+                player: self.player_to_move,
+                next_sub_board: self.next_sub_board as u32,
+                position_hash: self.snapshot_hash(),
+            }),
+        }
+    }
+
+    /// Returns a hash of the complete position. Unlike [`std::hash::Hash`], the value is stable
+    /// across platforms, Rust versions and runs, so it can be used to identify positions in logs.
+    pub fn snapshot_hash(&self) -> u64 {
+        // FNV-1a over all the fields of the board.
+        const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const PRIME: u64 = 0x100000001b3;
+        let words = self
+            .board
+            .iter()
+            .flat_map(|sub_board| [sub_board.x.0, sub_board.o.0])
+            .chain([self.sub_wins.x.0, self.sub_wins.o.0, self.sub_wins.tie.0])
+            .map(u32::from)
+            .chain([self.player_to_move as u32, self.next_sub_board as u32]);
+        words.fold(OFFSET_BASIS, |hash, word| {
+            word.to_le_bytes()
+                .iter()
+                .fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+        })
+    }
+
+    /// Writes the legal moves into `moves` and returns the initialized prefix of the buffer. The
+    /// buffer is large enough for every position, so this never allocates.
+    ///
+    /// [`move_list`](Self::move_list) does the same without a buffer from the caller.
+    pub fn generate_moves_in_place<'a>(&self, moves: &'a mut [Move; 81]) -> &'a [Move] {
+        let mut len = 0;
+        self.for_each_move(|m| {
+            moves[len] = m;
+            len += 1;
+        });
+        &moves[..len]
+    }
+
+    /// Returns the legal moves in a [`MoveList`], in the same order as
+    /// [`generate_moves`](Self::generate_moves). This never allocates.
+    pub fn move_list(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        self.generate_moves_into(&mut moves);
+        moves
+    }
+
+    /// Replaces the moves of `moves` with the legal moves. Loops that generate moves for many
+    /// positions should reuse one [`MoveList`] with this instead of calling
+    /// [`move_list`](Self::move_list), which has to initialize a new list every time.
+    pub fn generate_moves_into(&self, moves: &mut MoveList) {
+        moves.clear();
+        self.for_each_move(|m| moves.push(m));
+    }
+
+    /// Calls `push` with every legal move, ordered by major and then minor index.
+    #[inline(always)]
+    fn for_each_move(&self, mut push: impl FnMut(Move)) {
+        if self.is_closed() {
+            return;
+        }
+        match self.next_sub_board {
+            0..=8 => {
+                // Can only move in a specific sub-board.
+                let sub_board = self.board[self.next_sub_board as usize];
+                let or = sub_board.x.0 | sub_board.o.0;
+                for i in 0..=8 {
+                    if or & 1 << i == 0 {
+                        push(Move {
+                            major: self.next_sub_board as u32,
+                            minor: i,
+                        });
+                    }
+                }
+            }
+            9 => {
+                // Can move in any open spot that is not already won.
+                for i in 0..=8 {
+                    if self.sub_wins.x.0 & 1 << i == 0
+                        && self.sub_wins.o.0 & 1 << i == 0
+                        && self.sub_wins.tie.0 & 1 << i == 0
+                    {
+                        let sub_board = self.board[i];
+                        let or = sub_board.x.0 | sub_board.o.0;
+                        // Sub-board is available. Generate moves for sub-board.
+                        for j in 0..=8 {
+                            if or & 1 << j == 0 {
+                                push(Move {
+                                    major: i as u32,
+                                    minor: j,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+            _ => unreachable!("invalid value for self.next_sub_board"),
+        }
+    }
+
+    /// Returns the sub-boards the player to move can play in as a bitmask where bit `major` is set
+    /// for every playable sub-board. This is every undecided sub-board if the
+    /// [`constraint`](Self::constraint) is [`NextSubBoard::Any`], and no sub-board once the game is over
+    /// under [`Rules::strict_game_over`].
+    pub fn playable_sub_boards(&self) -> u16 {
+        if self.is_closed() {
+            return 0;
+        }
+        match self.constraint() {
+            NextSubBoard::Any => {
+                let decided = self.sub_wins.x.0 | self.sub_wins.o.0 | self.sub_wins.tie.0;
+                !decided & 0b111111111
+            }
+            NextSubBoard::Sub(major) => 1 << major,
+        }
+    }
+
+    /// Returns the legal moves as a bitmask where bit `major * 9 + minor` is set for every legal
+    /// move. The bit of a move is its [`PackedMove::index`], and [`MovesIter::from_mask`] turns
+    /// the set bits of a mask back into moves.
+    pub fn legal_move_mask(&self) -> u128 {
+        let playable = self.playable_sub_boards();
+        let mut mask = 0;
+        for major in 0..9 {
+            if playable & 1 << major != 0 {
+                let sub_board = self.board[major as usize];
+                let empty = !(sub_board.x.0 | sub_board.o.0) & 0b111111111;
+                mask |= (empty as u128) << (major * 9);
+            }
+        }
+        mask
+    }
+
+    /// Returns the number of legal moves. This counts the empty cells of the playable sub-boards
+    /// without generating the moves.
+    pub fn count_moves(&self) -> u32 {
+        let playable = self.playable_sub_boards();
+        self.board
+            .iter()
+            .enumerate()
+            .filter(|(major, _)| playable & 1 << major != 0)
+            .map(|(_, sub_board)| 9 - (sub_board.x.0 | sub_board.o.0).count_ones())
+            .sum()
+    }
+
+    /// Returns an iterator over the legal moves, in the same order as
+    /// [`generate_moves`](Self::generate_moves), without allocating.
+    pub fn moves(&self) -> MovesIter {
+        MovesIter::from_mask(self.legal_move_mask())
+    }
+
+    pub fn generate_moves(&self) -> Vec<Move> {
+        self.move_list().to_vec()
+    }
+
+    /// Returns the major indices of the three sub-boards that won the game, in increasing order, or
+    /// `None` if the game is not won by a player.
+    pub fn winning_line(&self) -> Option<[u32; 3]> {
+        let sub_wins = match self.winner()? {
+            Outcome::X => self.sub_wins.x,
+            Outcome::O => self.sub_wins.o,
+            Outcome::Tie => return None,
+        };
+        let pattern = sub_wins.winning_pattern()?.0;
+        let mut line = [0; 3];
+        for (slot, major) in line.iter_mut().zip(BitBoard(pattern).iter_ones()) {
+            *slot = major;
+        }
+        Some(line)
+    }
+
+    /// Returns the [`Outcome`] of the game or `None` if the game is still in progress.
+    pub fn winner(&self) -> Option<Outcome> {
+        if self.sub_wins.x.has_winner() == HasWinner::Yes {
+            Some(Outcome::X)
+        } else if self.sub_wins.o.has_winner() == HasWinner::Yes {
+            Some(Outcome::O)
+        } else if self.sub_wins.x.0 | self.sub_wins.o.0 | self.sub_wins.tie.0 == 0b111111111 {
+            Some(Outcome::Tie)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of moves played to reach this position.
+    pub fn move_count(&self) -> u32 {
+        self.ply as u32
+    }
+
+    /// Returns the move that led to this position, or `None` if no move was played on this board,
+    /// e.g. for the initial position or a position parsed from [notation](Self::from_notation).
+    pub fn last_move(&self) -> Option<Move> {
+        self.last_move.map(Move::from)
+    }
+
+    /// Returns the number of cells occupied by `player`. The cells are counted rather than derived
+    /// from the [move count](Self::move_count), since X did not necessarily move first, e.g. in a
+    /// [swapped](Self::swapped) position.
+    pub fn stones(&self, player: Player) -> u32 {
+        self.board
+            .iter()
+            .map(|sub_board| sub_board[player].0.count_ones())
+            .sum()
+    }
+
+    /// Returns the player occupying the cell `minor` of sub-board `major`, or `None` if the cell is
+    /// empty.
+    ///
+    /// # Panics
+    /// This method panics if `major` or `minor` is not in the range `0..9`.
+    pub fn cell(&self, major: u32, minor: u32) -> Option<Player> {
+        assert!(minor < 9, "minor index {} is out of range", minor);
+        let sub_board = self.board[major as usize];
+        if sub_board.x.0 & 1 << minor != 0 {
+            Some(Player::X)
+        } else if sub_board.o.0 & 1 << minor != 0 {
+            Some(Player::O)
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over all 81 cells as `(major, minor, player)`, where `player` is the
+    /// player occupying the cell or `None` if it is empty. Cells are ordered by sub-board, then by
+    /// cell.
+    pub fn cells(&self) -> impl Iterator<Item = (u32, u32, Option<Player>)> + '_ {
+        (0..9)
+            .flat_map(move |major| (0..9).map(move |minor| (major, minor, self.cell(major, minor))))
+    }
+
+    /// Returns an iterator over the empty cells as moves, in the same order as
+    /// [`cells`](Self::cells). Unlike [`generate_moves`](Self::generate_moves), this includes
+    /// cells that cannot be played in.
+    pub fn empty_cells(&self) -> impl Iterator<Item = Move> + '_ {
+        self.cells()
+            .filter(|&(_, _, player)| player.is_none())
+            .map(|(major, minor, _)| Move { major, minor })
+    }
+
+    /// Returns the [`Outcome`] of sub-board `major` or `None` if it is still in progress.
+    ///
+    /// # Panics
+    /// This method panics if `major` is not in the range `0..9`.
+    pub fn sub_board_winner(&self, major: u32) -> Option<Outcome> {
+        assert!(major < 9, "major index {} is out of range", major);
+        if self.sub_wins.x.0 & 1 << major != 0 {
+            Some(Outcome::X)
+        } else if self.sub_wins.o.0 & 1 << major != 0 {
+            Some(Outcome::O)
+        } else if self.sub_wins.tie.0 & 1 << major != 0 {
+            Some(Outcome::Tie)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the position with the stones of X and O exchanged and the other player to move.
+    /// The next sub-board stays the same, so the player to move faces the same position as the
+    /// opponent would in this one. This is useful for evaluating positions from the perspective
+    /// of a single player.
+    ///
+    /// Since X moves first, the swapped position is usually not reachable in a real game and is
+    /// rejected by [`validate`](Self::validate).
+    pub fn swapped(&self) -> Self {
+        let mut board = *self;
+        for sub_board in &mut board.board {
+            std::mem::swap(&mut sub_board.x, &mut sub_board.o);
+        }
+        std::mem::swap(&mut board.sub_wins.x, &mut board.sub_wins.o);
+        board.player_to_move = self.player_to_move.opponent();
+        board.zobrist = board.compute_zobrist_hash();
+        board
+    }
+}
+
+/// Compares the positions and the [`Rules`]. The [last move](Board::last_move) is ignored, so
+/// transpositions are equal.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.sub_wins == other.sub_wins
+            && self.board == other.board
+            && self.player_to_move == other.player_to_move
+            && self.next_sub_board == other.next_sub_board
+            && self.rules == other.rules
+            && self.ply == other.ply
+            && self.zobrist == other.zobrist
+    }
+}
+
+impl Eq for Board {}
+
+/// Hashes the [Zobrist hash](Board::zobrist_hash) of the position. Unlike [`PartialEq`], this ignores
+/// the [`Rules`].
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.zobrist.hash(state);
+    }
+}
+
+/// Shows the position in [notation](Board::to_notation) along with the [`Rules`] and the
+/// [last move](Board::last_move).
+impl fmt::Debug for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Board")
+            .field("position", &self.to_notation())
+            .field("rules", &self.rules)
+            .field("last_move", &self.last_move())
+            .finish()
+    }
+}
+
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for major_row in 0..3 {
+            for minor_row in 0..3 {
+                for major_col in 0..3 {
+                    for minor_col in 0..3 {
+                        let major = major_row * 3 + major_col;
+                        let minor = minor_row * 3 + minor_col;
+
+                        let sub_board = self.board[major];
+                        let mask = 1 << minor;
+                        if sub_board.x.0 & mask != 0 {
+                            write!(f, "X")?;
+                        } else if sub_board.o.0 & mask != 0 {
+                            write!(f, "O")?;
+                        } else {
+                            write!(f, "_")?;
+                        }
+
+                        write!(f, " ")?;
+                    }
+                    write!(f, "  ")?;
+                }
+                writeln!(f)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubBoard {
+    pub x: BitBoard,
+    pub o: BitBoard,
+}
+
+impl Index<Player> for SubBoard {
+    type Output = BitBoard;
+
+    fn index(&self, player: Player) -> &Self::Output {
+        match player {
+            Player::X => &self.x,
+            Player::O => &self.o,
+        }
+    }
+}
+
+impl IndexMut<Player> for SubBoard {
+    fn index_mut(&mut self, player: Player) -> &mut Self::Output {
+        match player {
+            Player::X => &mut self.x,
+            Player::O => &mut self.o,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WinBoard {
+    pub x: BitBoard,
+    pub o: BitBoard,
+    pub tie: BitBoard,
+}
+
+/// Indexes the sub-boards won by a player.
+impl Index<Player> for WinBoard {
+    type Output = BitBoard;
+
+    fn index(&self, player: Player) -> &Self::Output {
+        match player {
+            Player::X => &self.x,
+            Player::O => &self.o,
+        }
+    }
+}
+
+impl IndexMut<Player> for WinBoard {
+    fn index_mut(&mut self, player: Player) -> &mut Self::Output {
+        match player {
+            Player::X => &mut self.x,
+            Player::O => &mut self.o,
+        }
+    }
+}
+
+/// The rows, columns and diagonals of a 3x3 grid as bit masks.
+pub(crate) const WIN_CONFIGURATIONS: [u16; 8] = [
+    0b111000000,
+    0b000111000,
+    0b000000111,
+    0b100100100,
+    0b010010010,
+    0b001001001,
+    0b100010001,
+    0b001010100,
+];
+
+/// Bit of a [`LINE_TABLE`] entry that is set if the bit board contains three in a row.
+const HAS_LINE: u16 = 1 << 9;
+
+/// Lookup table indexed by the 9 cells of a [`BitBoard`]. The low 9 bits of an entry are the cells
+/// that complete a line when added to the bit board, and [`HAS_LINE`] is set if it already
+/// contains one. The table is generated at compile time.
+const LINE_TABLE: [u16; 512] = {
+    let mut table = [0; 512];
+    let mut board = 0;
+    while board < 512 {
+        let mut entry = 0;
+        let mut i = 0;
+        while i < WIN_CONFIGURATIONS.len() {
+            let missing = WIN_CONFIGURATIONS[i] & !board;
+            if missing == 0 {
+                entry |= HAS_LINE;
+            } else if missing.count_ones() == 1 {
+                entry |= missing;
+            }
+            i += 1;
+        }
+        table[board as usize] = entry;
+        board += 1;
+    }
+    table
+};
+
+/// A `u16` bit board.
+///
+/// Only the first 9 bits are used for representing the board state.
+/// `0` represents an empty cell, `1` represents an X.
+///
+/// The remaining bits are unused and should always be `0`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitBoard(pub u16);
+
+impl Default for BitBoard {
+    /// Default for a `BitBoard` is `0` (all cells are empty).
+    fn default() -> Self {
+        Self(0)
+    }
+}
+
+impl BitBoard {
+    /// Check if the bit board has a winning configuration.
+    /// This is done by checking if the bit board matches one of the winning patterns.
+    pub fn has_winner(self) -> HasWinner {
+        // Check for win.
+        if self.has_line() {
+            return HasWinner::Yes;
+        }
+        // Check for tie.
+        if self.0 == 0b111111111 {
+            return HasWinner::Tie;
+        }
+        HasWinner::InProgress
+    }
+
+    /// Returns `true` if the bit board contains three in a row.
+    ///
+    /// This uses [`has_line_table`](Self::has_line_table). In isolation, the table lookup is about 3
+    /// times faster than [`has_line_patterns`](Self::has_line_patterns) and 2 times faster than
+    /// [`has_line_shifts`](Self::has_line_shifts), although the difference is within the noise of
+    /// the `bench` command.
+    #[inline(always)]
+    pub fn has_line(self) -> bool {
+        self.has_line_table()
+    }
+
+    /// Checks for three in a row by looking up the bit board in a table of all 512 bit boards.
+    #[inline(always)]
+    pub fn has_line_table(self) -> bool {
+        LINE_TABLE[(self.0 & 0b111111111) as usize] & HAS_LINE != 0
+    }
+
+    /// Returns the cells that complete three in a row for this bit board, leaving out the cells in
+    /// `occupied`. `occupied` is usually all cells occupied by either player, so that the result
+    /// is the moves that win the sub-board right away. Tactical code should call
+    /// [`completing_moves`](Self::completing_moves).
+    #[inline(always)]
+    pub fn win_in_one_mask(self, occupied: BitBoard) -> BitBoard {
+        BitBoard(LINE_TABLE[(self.0 & 0b111111111) as usize] & 0b111111111 & !occupied.0)
+    }
+
+    /// Returns the empty cells that complete three in a row for this bit board, where `occupied`
+    /// is the cells occupied by either player. This looks up
+    /// [`win_in_one_mask`](Self::win_in_one_mask).
+    ///
+    /// Called on the bit board of the player to move, the result is the winning moves. Called on
+    /// the bit board of the opponent, it is the moves that block a win. The same works for the
+    /// whole board with [`Board::sub_wins`], where `occupied` is the decided sub-boards.
+    #[inline(always)]
+    pub fn completing_moves(self, occupied: BitBoard) -> BitBoard {
+        self.win_in_one_mask(occupied)
+    }
+
+    /// Checks for three in a row by matching the bit board against each of the 8 winning
+    /// patterns.
+    #[inline(always)]
+    pub fn has_line_patterns(self) -> bool {
+        WIN_CONFIGURATIONS
+            .into_iter()
+            .any(|win_config| self.0 & win_config == win_config)
+    }
+
+    /// Create a [`BitBoard`] with the cells at the given positions set.
+    ///
+    /// # Panics
+    /// This method panics if a position is greater than 8.
+    pub fn from_positions(positions: &[u32]) -> Self {
+        positions.iter().fold(Self::default(), |bit_board, &pos| {
+            assert!(pos <= 8, "position {} is out of range", pos);
+            bit_board.advance_bitfield_state(pos)
+        })
+    }
+
+    /// Returns an iterator over the positions of the set cells, in increasing order.
+    pub fn iter_ones(self) -> impl Iterator<Item = u32> {
+        let mut bits = self.0 & 0b111111111;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let pos = bits.trailing_zeros();
+            // Clear the lowest set bit.
+            bits &= bits - 1;
+            Some(pos)
+        })
+    }
+
+    /// Returns the first of the 8 winning patterns, i.e. the rows, columns and diagonals, that the
+    /// bit board contains, or `None` if it does not contain three in a row.
+    pub fn winning_pattern(self) -> Option<BitBoard> {
+        WIN_CONFIGURATIONS
+            .into_iter()
+            .find(|&win_config| self.0 & win_config == win_config)
+            .map(BitBoard)
+    }
+
+    /// Counts the rows, columns and diagonals that contain none of the `opponent` cells, by the
+    /// number of cells of this bit board in them. Returns the number of lines with 1, 2 and 3 of
+    /// the cells of this bit board.
+    ///
+    /// Lines with 2 cells are threats to win the sub-board, or the game when called on
+    /// [`Board::sub_wins`] with the sub-boards that are won by the opponent or tied as `opponent`.
+    pub fn open_lines(self, opponent: BitBoard) -> (u32, u32, u32) {
+        let mut counts = [0; 4];
+        for line in WIN_CONFIGURATIONS {
+            if opponent.0 & line == 0 {
+                counts[(self.0 & line).count_ones() as usize] += 1;
+            }
+        }
+        (counts[1], counts[2], counts[3])
+    }
+
+    /// Checks for three in a row in closed form, without looping over the winning patterns.
+    ///
+    /// Cell `row * 3 + col` is stored in bit `row * 3 + col`. Shifting the board right by 1 and 2
+    /// and AND-ing the results leaves a bit at the start of every full row, and the same with
+    /// shifts of 3 and 6 for columns. The two diagonals are checked directly.
+    #[inline(always)]
+    pub fn has_line_shifts(self) -> bool {
+        let b = self.0;
+        let rows = b & (b >> 1) & (b >> 2) & 0b001001001;
+        let cols = b & (b >> 3) & (b >> 6) & 0b000000111;
+        let diagonal = (b & 0b100010001 == 0b100010001) as u16;
+        let anti_diagonal = (b & 0b001010100 == 0b001010100) as u16;
+        rows | cols | diagonal | anti_diagonal != 0
+    }
+
+    /// Returns the bit board with the position of the move applied onto it. Does not change the
+    /// original bit board.
+    ///
+    /// Internally, this function sets the bit corresponding to the position which should be in the
+    /// range from `0` to `8` inclusive.
+    #[must_use = "advanced_state does not modify original BitBoard"]
+    #[inline(always)]
+    pub fn advance_bitfield_state(self, pos: u32) -> Self {
+        let bit = 1 << pos;
+        Self(self.0 | bit)
+    }
+}
+
+/// Shows the cells in binary, with cell `0` as the last digit.
+impl fmt::Debug for BitBoard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "BitBoard({:#011b})", self.0)
+    }
+}
+
+impl From<[bool; 9]> for BitBoard {
+    /// Sets the cells whose position is `true`.
+    fn from(cells: [bool; 9]) -> Self {
+        cells
+            .into_iter()
+            .enumerate()
+            .fold(Self::default(), |bit_board, (pos, set)| {
+                Self(bit_board.0 | (set as u16) << pos)
+            })
+    }
+}
+
+impl From<BitBoard> for [bool; 9] {
+    fn from(bit_board: BitBoard) -> Self {
+        std::array::from_fn(|pos| bit_board.0 & 1 << pos != 0)
+    }
+}
+
+impl BitAnd for BitBoard {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for BitBoard {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Represents a position on the board. Does not store the player who applies the move.
+///
+/// Moves are ordered by their major index and then by their minor index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Move {
+    /// The major index (position of the sub-board) of the move.
+    /// Range can be assumed to be between 0 and 8 inclusive.
+    pub major: u32,
+    /// The minor index (position of the cell within a sub-board) of the move.
+    /// Range can be assumed to be between 0 and 8 inclusive.
+    pub minor: u32,
+}
+
+impl Move {
+    /// Create a new [`Move`].
+    ///
+    /// # Panics
+    /// This method panics if the major index is greater than 8 or the minor index is greater than
+    /// 8.
+    pub fn new(major: u32, minor: u32) -> Self {
+        assert!(major <= 8);
+        assert!(minor <= 8);
+        Self { major, minor }
+    }
+
+    /// Create a [`Move`] from the row and column of its cell in the 9x9 grid. Rows are numbered
+    /// from top to bottom and columns from left to right, both starting at `0`.
+    ///
+    /// # Panics
+    /// This method panics if the row or the column is greater than 8.
+    pub fn from_global(row: u32, col: u32) -> Self {
+        assert!(row <= 8);
+        assert!(col <= 8);
+        Self::new(row / 3 * 3 + col / 3, row % 3 * 3 + col % 3)
+    }
+
+    /// Returns the row and column of the cell of the move in the 9x9 grid, see
+    /// [`from_global`](Self::from_global).
+    pub fn global(&self) -> (u32, u32) {
+        (
+            self.major / 3 * 3 + self.minor / 3,
+            self.major % 3 * 3 + self.minor % 3,
+        )
+    }
+}
+
+/// Formats the move as `major,minor`, e.g. `4,0`, which is the format parsed by [`FromStr`].
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for Move {
+    type Err = ParseError;
+
+    /// Parses a move of the form `major,minor`, e.g. `4,0`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::InvalidMove(s.to_string());
+        let (major, minor) = s.split_once(',').ok_or_else(invalid)?;
+        let major = major.trim().parse().map_err(|_| invalid())?;
+        let minor = minor.trim().parse().map_err(|_| invalid())?;
+        if major > 8 || minor > 8 {
+            return Err(MoveError::OutOfRange { major, minor }.into());
+        }
+        Ok(Self { major, minor })
+    }
+}
+
+/// A [`Move`] packed into a single byte, for storing many moves compactly, e.g. in search trees.
+///
+/// The byte is the index `major * 9 + minor` of the move, so packed moves are ordered the same way
+/// as moves and can be used as indices into the bits of [`Board::legal_move_mask`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedMove(u8);
+
+impl PackedMove {
+    /// Create a [`PackedMove`] from its index `major * 9 + minor`.
+    ///
+    /// # Panics
+    /// This method panics if the index is greater than 80.
+    pub fn from_index(index: u8) -> Self {
+        assert!(index < 81);
+        Self(index)
+    }
+
+    /// Returns the index `major * 9 + minor` of the move.
+    pub fn index(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<Move> for PackedMove {
+    fn from(m: Move) -> Self {
+        Self((m.major * 9 + m.minor) as u8)
+    }
+}
+
+impl From<PackedMove> for Move {
+    fn from(m: PackedMove) -> Self {
+        Self {
+            major: m.0 as u32 / 9,
+            minor: m.0 as u32 % 9,
+        }
+    }
+}
+
+/// Iterator over the legal moves of a [`Board`], see [`Board::moves`], or over the moves of any
+/// bitmask in the layout of [`Board::legal_move_mask`].
+#[derive(Debug, Clone)]
+pub struct MovesIter {
+    /// The moves that have not been returned yet.
+    mask: u128,
+}
+
+impl MovesIter {
+    /// Mask of the 81 bits that correspond to a move.
+    const ALL_MOVES: u128 = (1 << 81) - 1;
+
+    /// Create an iterator over the moves whose bit `major * 9 + minor` is set in `mask`, in
+    /// increasing order. Bits above the 81st are ignored.
+    pub fn from_mask(mask: u128) -> Self {
+        Self {
+            mask: mask & Self::ALL_MOVES,
+        }
+    }
+}
+
+impl Iterator for MovesIter {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        if self.mask == 0 {
+            return None;
+        }
+        let index = self.mask.trailing_zeros();
+        // Clear the lowest set bit.
+        self.mask &= self.mask - 1;
+        Some(PackedMove(index as u8).into())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.mask.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for MovesIter {}
+
+impl std::iter::FusedIterator for MovesIter {}
+
+/// What [`Board::unmake_move`] needs to take back a move made with [`Board::make_move_mut`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UndoInfo {
+    m: Move,
+    sub_wins: WinBoard,
+    next_sub_board: u8,
+    zobrist: u64,
+    last_move: Option<PackedMove>,
+}
+
+impl UndoInfo {
+    /// Returns the move that was made.
+    pub fn m(&self) -> Move {
+        self.m
+    }
+}
+
+/// A list of moves stored inline, with room for the 81 moves of the largest position, so it never
+/// allocates. It dereferences to a slice of the moves in it.
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; 81],
+    len: usize,
+}
+
+impl MoveList {
+    /// Create an empty [`MoveList`].
+    pub fn new() -> Self {
+        Self {
+            moves: [Move::new(0, 0); 81],
+            len: 0,
+        }
+    }
+
+    /// Appends a move to the list.
+    ///
+    /// # Panics
+    /// This method panics if the list already has 81 moves.
+    pub fn push(&mut self, m: Move) {
+        self.moves[self.len] = m;
+        self.len += 1;
+    }
+
+    /// Removes all moves from the list.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl std::ops::Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl std::ops::DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,150 @@
+//! The 8 symmetries of the board.
+//!
+//! Rotating or reflecting the whole board moves every sub-board and every cell inside of it the
+//! same way, so positions that are symmetric to each other have the same value and symmetric best
+//! moves.
+
+use crate::{BitBoard, Board, Move, NextSubBoard, SubBoard, WinBoard};
+
+/// A rotation or reflection of the board. Rotations are clockwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Reflection across the vertical axis, swapping the left and right columns.
+    FlipHorizontal,
+    /// Reflection across the horizontal axis, swapping the top and bottom rows.
+    FlipVertical,
+    /// Reflection across the diagonal from the top left to the bottom right.
+    FlipDiagonal,
+    /// Reflection across the diagonal from the top right to the bottom left.
+    FlipAntiDiagonal,
+}
+
+/// Where each cell `row * 3 + col` of a 3x3 grid ends up under each [`Symmetry`].
+const PERMUTATIONS: [[u32; 9]; 8] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8],
+    [2, 5, 8, 1, 4, 7, 0, 3, 6],
+    [8, 7, 6, 5, 4, 3, 2, 1, 0],
+    [6, 3, 0, 7, 4, 1, 8, 5, 2],
+    [2, 1, 0, 5, 4, 3, 8, 7, 6],
+    [6, 7, 8, 3, 4, 5, 0, 1, 2],
+    [0, 3, 6, 1, 4, 7, 2, 5, 8],
+    [8, 5, 2, 7, 4, 1, 6, 3, 0],
+];
+
+impl Symmetry {
+    /// All symmetries, starting with [`Symmetry::Identity`].
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipVertical,
+        Symmetry::FlipDiagonal,
+        Symmetry::FlipAntiDiagonal,
+    ];
+
+    /// Returns the symmetry that undoes this one.
+    pub fn inverse(self) -> Self {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Maps the index of a cell in a 3x3 grid, i.e. a `major` or `minor` index.
+    ///
+    /// # Panics
+    /// This method panics if `index` is not in the range `0..9`.
+    pub fn apply_index(self, index: u32) -> u32 {
+        PERMUTATIONS[self as usize][index as usize]
+    }
+
+    /// Maps a move of a position to the same move in the transformed position.
+    pub fn apply_move(self, m: Move) -> Move {
+        Move {
+            major: self.apply_index(m.major),
+            minor: self.apply_index(m.minor),
+        }
+    }
+
+    /// Maps the cells of a [`BitBoard`].
+    pub fn apply_bit_board(self, bit_board: BitBoard) -> BitBoard {
+        let permutation = &PERMUTATIONS[self as usize];
+        let mut result = 0;
+        for (from, &to) in permutation.iter().enumerate() {
+            result |= (bit_board.0 >> from & 1) << to;
+        }
+        BitBoard(result)
+    }
+}
+
+impl Board {
+    /// Returns the position transformed by `symmetry`.
+    pub fn transformed(&self, symmetry: Symmetry) -> Self {
+        let mut board = *self;
+        for (major, sub_board) in self.board.iter().enumerate() {
+            board.board[symmetry.apply_index(major as u32) as usize] = SubBoard {
+                x: symmetry.apply_bit_board(sub_board.x),
+                o: symmetry.apply_bit_board(sub_board.o),
+            };
+        }
+        board.sub_wins = WinBoard {
+            x: symmetry.apply_bit_board(self.sub_wins.x),
+            o: symmetry.apply_bit_board(self.sub_wins.o),
+            tie: symmetry.apply_bit_board(self.sub_wins.tie),
+        };
+        if let NextSubBoard::Sub(major) = self.constraint() {
+            board.next_sub_board = symmetry.apply_index(major as u32) as u8;
+        }
+        board.last_move = self.last_move().map(|m| symmetry.apply_move(m).into());
+        board.zobrist = board.compute_zobrist_hash();
+        board
+    }
+
+    /// Returns the canonical representative of the position among its 8 symmetric equivalents and
+    /// the symmetry that transforms this position into it. Symmetric positions have the same
+    /// canonical position, so it can be used as a key in opening books and transposition tables.
+    ///
+    /// Moves of this position are mapped to the canonical position with
+    /// [`Symmetry::apply_move`], and back with the [inverse](Symmetry::inverse).
+    ///
+    /// The canonical position is the lexicographically smallest one when comparing the cells of X
+    /// and then O of every sub-board in order, and then the next sub-board.
+    pub fn canonical(&self) -> (Self, Symmetry) {
+        let key = |board: &Board| {
+            let mut key = [0; 19];
+            for (major, sub_board) in board.board.iter().enumerate() {
+                key[major * 2] = sub_board.x.0 as u32;
+                key[major * 2 + 1] = sub_board.o.0 as u32;
+            }
+            key[18] = board.next_sub_board as u32;
+            key
+        };
+        Symmetry::ALL
+            .into_iter()
+            .map(|symmetry| (self.transformed(symmetry), symmetry))
+            .min_by_key(|(board, _)| key(board))
+            .expect("there are 8 symmetries")
+    }
+
+    /// Returns `true` if `other` is this position transformed by one of the 8 symmetries,
+    /// including the identity. Like [`PartialEq`], this also compares the [`Rules`](crate::Rules).
+    pub fn symmetric_eq(&self, other: &Board) -> bool {
+        self.move_count() == other.move_count()
+            && Symmetry::ALL
+                .into_iter()
+                .any(|symmetry| self.transformed(symmetry) == *other)
+    }
+
+    /// Returns the [Zobrist hash](Self::zobrist_hash) of the [canonical](Self::canonical)
+    /// position, which is the same for all symmetric positions.
+    pub fn canonical_hash(&self) -> u64 {
+        self.canonical().0.zobrist_hash()
+    }
+}
@@ -0,0 +1,227 @@
+//! Consistency checks for positions that were not reached by playing moves.
+
+use crate::{BitBoard, Board, NextSubBoard, Player, PositionError};
+
+const FULL: u16 = 0b111111111;
+/// How many moves [`Board::is_reachable`] takes back. Every level multiplies the work by the
+/// number of candidate moves, so the whole history is not searched.
+const RETRACTION_DEPTH: u32 = 4;
+
+impl Board {
+    /// Recomputes `sub_wins`, the [move count](Self::move_count) and the
+    /// [Zobrist hash](Self::zobrist_hash) from the cells, after the public fields of the board were
+    /// changed directly.
+    pub fn update_derived_state(&mut self) {
+        self.recompute_sub_wins();
+        self.ply = self.count_cells() as u8;
+        self.zobrist = self.compute_zobrist_hash();
+    }
+
+    /// Recomputes only `sub_wins` from the cells. With the `simd` feature on x86_64, all
+    /// sub-boards are checked at once with SIMD instructions.
+    pub fn recompute_sub_wins(&mut self) {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            self.sub_wins = crate::simd::sub_wins(&self.board);
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            self.sub_wins = Default::default();
+            for (major, sub_board) in self.board.iter().enumerate() {
+                let mask = 1 << major;
+                let x_won = sub_board.x.has_line();
+                let o_won = sub_board.o.has_line();
+                if x_won {
+                    self.sub_wins.x.0 |= mask;
+                }
+                if o_won {
+                    self.sub_wins.o.0 |= mask;
+                }
+                if !x_won && !o_won && sub_board.x.0 | sub_board.o.0 == FULL {
+                    self.sub_wins.tie.0 |= mask;
+                }
+            }
+        }
+    }
+
+    /// Returns the number of occupied cells, without relying on the stored move count.
+    fn count_cells(&self) -> u32 {
+        self.board
+            .iter()
+            .map(|sub_board| (sub_board.x.0 | sub_board.o.0).count_ones())
+            .sum()
+    }
+
+    /// Checks that the position is consistent and can occur in a game, so that it is safe to
+    /// search and play moves in:
+    ///
+    /// - No cell is occupied by both players and no bits outside of the 9 cells are set.
+    /// - No sub-board is won by both players, and `sub_wins` matches the cells.
+    /// - The numbers of cells of both players match the player to move, since X moves first.
+    /// - The [constraint](Self::constraint) is anywhere or an undecided sub-board, and follows from
+    ///   a cell of the previous player: the last move was played in the cell with the index of the
+    ///   next sub-board, or in a decided sub-board if the player to move can move anywhere.
+    /// - The move count and Zobrist hash match the cells.
+    ///
+    /// Positions reached by playing legal moves from [`Board::new`] always pass.
+    pub fn validate(&self) -> Result<(), PositionError> {
+        let decided = self.sub_wins.x.0 | self.sub_wins.o.0 | self.sub_wins.tie.0;
+        for (major, sub_board) in self.board.iter().enumerate() {
+            let (x, o) = (sub_board.x.0, sub_board.o.0);
+            let major = major as u32;
+            if x & o != 0 || (x | o) & !FULL != 0 {
+                return Err(PositionError::InvalidCells { major });
+            }
+            let x_won = sub_board.x.has_line();
+            let o_won = sub_board.o.has_line();
+            if x_won && o_won {
+                return Err(PositionError::BothPlayersWon { major });
+            }
+            let mask = 1 << major;
+            let tie = !x_won && !o_won && x | o == FULL;
+            if (self.sub_wins.x.0 & mask != 0) != x_won
+                || (self.sub_wins.o.0 & mask != 0) != o_won
+                || (self.sub_wins.tie.0 & mask != 0) != tie
+            {
+                return Err(PositionError::SubWinsMismatch { major });
+            }
+        }
+        if decided & !FULL != 0 {
+            return Err(PositionError::SubWinsMismatch {
+                major: (decided & !FULL).trailing_zeros(),
+            });
+        }
+
+        let count = |player| {
+            self.board
+                .iter()
+                .map(|sub_board| sub_board[player].0.count_ones())
+                .sum::<u32>()
+        };
+        let (x, o) = (count(Player::X), count(Player::O));
+        let previous = match (self.player_to_move, x.checked_sub(o)) {
+            (Player::X, Some(0)) => Player::O,
+            (Player::O, Some(1)) => Player::X,
+            (player, _) => return Err(PositionError::WrongPlayerToMove { x, o, player }),
+        };
+
+        // The last move was played in the cell with the index of the next sub-board, in any
+        // sub-board.
+        let played = |minor: u32| {
+            self.board
+                .iter()
+                .any(|sub_board| sub_board[previous].0 & 1 << minor != 0)
+        };
+        match self.constraint() {
+            NextSubBoard::Any => {
+                if x > 0 && !BitBoard(decided).iter_ones().any(played) {
+                    return Err(PositionError::AnywhereUnreachable);
+                }
+            }
+            NextSubBoard::Sub(major) => {
+                let major = major as u32;
+                if decided & 1 << major != 0 {
+                    return Err(PositionError::NextSubBoardDecided { major });
+                }
+                if !played(major) {
+                    return Err(PositionError::NextSubBoardUnreachable { major });
+                }
+            }
+        }
+
+        if self.ply as u32 != self.count_cells() || self.zobrist != self.compute_zobrist_hash() {
+            return Err(PositionError::StaleDerivedState);
+        }
+        Ok(())
+    }
+
+    /// Checks that the position can arise from legal play, beyond [`validate`](Self::validate):
+    ///
+    /// - Every won sub-board has a stone of the winner without which it is undecided. This is the
+    ///   move that won it, after which nobody could play there.
+    /// - The last few moves can be taken back in a way that was legal: each was played in an
+    ///   undecided sub-board before the game was over, whatever the [`Rules`](crate::Rules), and
+    ///   sent the next player to where they moved. Only the last [`RETRACTION_DEPTH`] moves are
+    ///   checked, since trying every order of the whole game is too expensive, so some unreachable
+    ///   positions are accepted.
+    pub fn is_reachable(&self) -> bool {
+        if self.validate().is_err() {
+            return false;
+        }
+        for sub_board in &self.board {
+            for own in [sub_board.x, sub_board.o] {
+                if own.has_line()
+                    && !own
+                        .iter_ones()
+                        .any(|minor| !BitBoard(own.0 & !(1 << minor)).has_line())
+                {
+                    return false;
+                }
+            }
+        }
+        let targets = match self.constraint() {
+            NextSubBoard::Any => self.sub_wins.x.0 | self.sub_wins.o.0 | self.sub_wins.tie.0,
+            NextSubBoard::Sub(major) => 1 << major,
+        };
+        self.can_retract(targets, RETRACTION_DEPTH)
+    }
+
+    /// Returns `true` if a move of the previous player to a cell in `targets` can be taken back,
+    /// and so on for `depth` moves or until the board is empty. `targets` is a bitmask of the
+    /// minor indices that send the player to move to where they can move.
+    fn can_retract(&self, targets: u16, depth: u32) -> bool {
+        if depth == 0 || self.move_count() == 0 {
+            return true;
+        }
+        let previous = self.player_to_move.opponent();
+        for (major, sub_board) in self.board.iter().enumerate() {
+            for minor in BitBoard(sub_board[previous].0 & targets).iter_ones() {
+                let mut before = *self;
+                before.board[major][previous].0 &= !(1 << minor);
+                before.player_to_move = previous;
+                before.update_derived_state();
+                let decided = before.sub_wins.x.0 | before.sub_wins.o.0 | before.sub_wins.tie.0;
+                if decided & 1 << major != 0 || before.winner().is_some() {
+                    continue;
+                }
+                // The move before sent the previous player to this sub-board or anywhere.
+                if before.can_retract(1 << major | decided, depth - 1) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn moves_after_decided_game_are_unreachable() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut checked = 0;
+        for _ in 0..20 {
+            // Without `strict_game_over`, moves can still be played after the game is decided.
+            let mut board = Board::new();
+            while board.winner().is_none() {
+                let m = *board.generate_moves().choose(&mut rng).unwrap();
+                board = board.advance_state(m).unwrap();
+            }
+            assert!(board.is_reachable());
+            let Some(&m) = board.generate_moves().choose(&mut rng) else {
+                continue;
+            };
+            let after = board.advance_state(m).unwrap();
+            assert!(after.validate().is_ok());
+            assert!(!after.is_reachable());
+            checked += 1;
+        }
+        assert!(checked >= 10);
+    }
+}
@@ -0,0 +1,82 @@
+//! Zobrist hashing of positions.
+//!
+//! Every cell and player, the player to move and every value of the next sub-board get a random
+//! key. The hash of a position is the XOR of the keys of its features, so playing a move only
+//! needs to XOR in the keys that changed. The decided sub-boards follow from the cells and the
+//! [`Rules`](crate::Rules) are not part of the position, so neither is hashed.
+
+use crate::{Board, Player};
+
+/// Random keys of the features of a position.
+pub(crate) struct ZobristKeys {
+    /// Key of a cell occupied by a player, indexed by player and `major * 9 + minor`.
+    pub cells: [[u64; 81]; 2],
+    /// Key XOR-ed in if O is to move.
+    pub o_to_move: u64,
+    /// Key of each [constraint](Board::constraint), indexed by sub-board with `9` for anywhere.
+    pub next_sub_board: [u64; 10],
+}
+
+/// The keys are generated at compile time with SplitMix64 from a fixed seed, so hashes are stable
+/// across runs and platforms.
+pub(crate) const ZOBRIST: ZobristKeys = {
+    const fn split_mix(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    let mut state = 0x7574_7474_7273;
+    let mut keys = ZobristKeys {
+        cells: [[0; 81]; 2],
+        o_to_move: 0,
+        next_sub_board: [0; 10],
+    };
+    let mut i = 0;
+    while i < 81 {
+        keys.cells[0][i] = split_mix(&mut state);
+        keys.cells[1][i] = split_mix(&mut state);
+        i += 1;
+    }
+    keys.o_to_move = split_mix(&mut state);
+    let mut i = 0;
+    while i < 10 {
+        keys.next_sub_board[i] = split_mix(&mut state);
+        i += 1;
+    }
+    keys
+};
+
+impl Board {
+    /// Returns the Zobrist hash of the position, for use as a key in transposition tables and
+    /// position databases. The hash is updated incrementally by every move, so this is free.
+    ///
+    /// Unlike [`snapshot_hash`](Self::snapshot_hash), different positions can have the same hash,
+    /// although this is very unlikely. If the public fields of the board are changed directly, call
+    /// [`update_derived_state`](Self::update_derived_state) afterwards.
+    pub fn zobrist_hash(&self) -> u64 {
+        self.zobrist
+    }
+
+    pub(crate) fn compute_zobrist_hash(&self) -> u64 {
+        // An out of range next sub-board is reported by `validate`, so it only needs to not panic.
+        let mut hash = ZOBRIST
+            .next_sub_board
+            .get(self.next_sub_board as usize)
+            .copied()
+            .unwrap_or_default();
+        if self.player_to_move == Player::O {
+            hash ^= ZOBRIST.o_to_move;
+        }
+        for (major, sub_board) in self.board.iter().enumerate() {
+            for (player, keys) in [Player::X, Player::O].into_iter().zip(&ZOBRIST.cells) {
+                for minor in sub_board[player].iter_ones() {
+                    hash ^= keys[major * 9 + minor as usize];
+                }
+            }
+        }
+        hash
+    }
+}
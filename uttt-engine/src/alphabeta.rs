@@ -0,0 +1,382 @@
+//! Alpha-beta search, an alternative to the MCTS engine that is stronger in tactical positions and
+//! late in the game, where the static evaluation is reliable.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use uttt_core::{Board, Move, MoveList, EVAL_WIN};
+
+use crate::engine::eval_to_win_rate;
+use crate::{
+    Clock, Engine, EvalCache, InstantClock, MoveOrdering, ReplacementPolicy, SearchHandle,
+    SearchLimits, SearchResult, MAX_PLY,
+};
+
+/// Number of entries of the transposition table of an [`AlphaBetaEngine`].
+pub const ALPHA_BETA_TABLE_SIZE: usize = 1 << 20;
+/// Half the width of the aspiration window around the score of the previous iteration, in
+/// [`Board::evaluate`] units. The window is doubled every time the score falls outside of it.
+const ASPIRATION_WINDOW: i32 = 50;
+/// Scores beyond this are won or lost games, closer to the root the further they are from it.
+const WIN_THRESHOLD: i32 = EVAL_WIN - MAX_PLY as i32;
+/// Larger than every score.
+const INFINITY: i32 = EVAL_WIN + 1;
+/// Number of nodes between two clock reads.
+const NODES_PER_TIME_CHECK: u64 = 1024;
+
+/// What the score of a [`TableEntry`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The score is at least the stored one, since the search was cut off.
+    Lower,
+    /// The score is at most the stored one, since no move reached alpha.
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TableEntry {
+    depth: u32,
+    /// Score for the player to move. Won and lost games count from the position of the entry, see
+    /// [`score_to_table`].
+    score: i32,
+    bound: Bound,
+    best_move: Option<Move>,
+}
+
+/// Converts a score at `ply` below the root to one counting won games from the current position,
+/// so that the entry is valid wherever the position occurs in the tree.
+fn score_to_table(score: i32, ply: u32) -> i32 {
+    if score >= WIN_THRESHOLD {
+        score + ply as i32
+    } else if score <= -WIN_THRESHOLD {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Inverse of [`score_to_table`].
+fn score_from_table(score: i32, ply: u32) -> i32 {
+    if score >= WIN_THRESHOLD {
+        score - ply as i32
+    } else if score <= -WIN_THRESHOLD {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// Negamax search with alpha-beta pruning on [`Board::evaluate`].
+///
+/// The engine deepens the search one ply at a time (iterative deepening) until a limit is reached,
+/// using the result of each iteration for the next one: the best moves found are searched first,
+/// through a transposition table and [`MoveOrdering`], and each iteration starts with a narrow
+/// window around the previous score (aspiration windows). A won or lost game is recognized as
+/// soon as the search reaches its end, and faster wins are preferred.
+pub struct AlphaBetaEngine<C: Clock = InstantClock> {
+    board: Board,
+    clock: C,
+    ordering: MoveOrdering,
+    /// Kept across positions, since entries are keyed by [`Board::zobrist_hash`].
+    table: EvalCache<TableEntry>,
+    /// Set by [`SearchHandle::stop`].
+    stop: Arc<AtomicBool>,
+    /// Number of nodes searched by the current search.
+    nodes: u64,
+    /// Time on `clock` at which the current search stops.
+    deadline: Option<Duration>,
+    max_nodes: Option<u64>,
+    /// Whether the current search can stop before completing its iteration. The first iteration
+    /// always completes, so that there is a move to return.
+    can_abort: bool,
+    aborted: bool,
+    /// Best root move of the current iteration.
+    root_move: Option<Move>,
+}
+
+impl AlphaBetaEngine {
+    /// Create a new [`AlphaBetaEngine`] searching the given position.
+    pub fn for_position(board: Board) -> Self {
+        Self::with_clock(board, InstantClock::new())
+    }
+}
+
+impl<C: Clock> AlphaBetaEngine<C> {
+    /// Create a new [`AlphaBetaEngine`] searching the given position that measures time budgets
+    /// with the given [`Clock`].
+    pub fn with_clock(board: Board, clock: C) -> Self {
+        Self {
+            board,
+            clock,
+            ordering: MoveOrdering::new(),
+            table: EvalCache::new(ALPHA_BETA_TABLE_SIZE, ReplacementPolicy::Lru),
+            stop: Arc::new(AtomicBool::new(false)),
+            nodes: 0,
+            deadline: None,
+            max_nodes: None,
+            can_abort: false,
+            aborted: false,
+            root_move: None,
+        }
+    }
+
+    /// Returns the position being searched.
+    pub fn position(&self) -> Board {
+        self.board
+    }
+
+    /// Returns a handle that stops the searches of this engine from another thread. The search
+    /// stops as soon as its first iteration is complete, and returns the result of the last
+    /// complete iteration.
+    pub fn search_handle(&self) -> SearchHandle {
+        SearchHandle {
+            stop: self.stop.clone(),
+        }
+    }
+
+    /// Returns `true` if the search must stop now.
+    fn should_abort(&mut self) -> bool {
+        if !self.can_abort {
+            return false;
+        }
+        if self.stop.load(Ordering::Relaxed) && self.stop.swap(false, Ordering::Relaxed) {
+            return true;
+        }
+        if self.max_nodes.is_some_and(|max| self.nodes >= max) {
+            return true;
+        }
+        self.nodes.is_multiple_of(NODES_PER_TIME_CHECK)
+            && self
+                .deadline
+                .is_some_and(|deadline| self.clock.now() >= deadline)
+    }
+
+    /// Returns the score of `board` for the player to move, searched `depth` plies deep. `ply` is
+    /// the distance to the root. The score is exact if it lies strictly between `alpha` and
+    /// `beta`, otherwise it is a bound on the side of the window it falls on.
+    fn negamax(&mut self, board: Board, depth: u32, ply: u32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.should_abort() {
+            self.aborted = true;
+            return 0;
+        }
+        let eval = board.evaluate(board.player_to_move);
+        if board.winner().is_some() {
+            // Prefer faster wins and slower losses.
+            return eval.signum() * (EVAL_WIN - ply as i32);
+        }
+        if depth == 0 {
+            return eval;
+        }
+
+        let hash = board.zobrist_hash();
+        let mut table_move = None;
+        if let Some(&entry) = self.table.get(hash) {
+            table_move = entry.best_move;
+            if entry.depth >= depth && ply > 0 {
+                let score = score_from_table(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut moves = MoveList::new();
+        board.generate_moves_into(&mut moves);
+        self.ordering.order(&board, ply as usize, &mut moves);
+        // The best move of a previous search comes first.
+        if let Some(index) = moves.iter().position(|&m| Some(m) == table_move) {
+            moves[..=index].rotate_right(1);
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for &m in moves.iter() {
+            // SAFETY: m is a legal move.
+            let next = unsafe { board.advance_state_unsafe(m) };
+            let score = -self.negamax(next, depth - 1, ply + 1, -beta, -alpha);
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(m);
+                if ply == 0 {
+                    self.root_move = Some(m);
+                }
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                self.ordering
+                    .record_cutoff(board.player_to_move, ply as usize, m, depth);
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(
+            hash,
+            TableEntry {
+                depth,
+                score: score_to_table(best_score, ply),
+                bound,
+                best_move,
+            },
+        );
+        best_score
+    }
+
+    /// Searches the root `depth` plies deep within an aspiration window around `guess`, widening
+    /// the window until the score falls inside of it. Returns the score, or `None` if the search
+    /// was aborted.
+    fn search_root(&mut self, depth: u32, guess: Option<i32>) -> Option<i32> {
+        let mut window = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = match guess {
+            Some(guess) if guess.abs() < WIN_THRESHOLD => (guess - window, guess + window),
+            _ => (-INFINITY, INFINITY),
+        };
+        loop {
+            let score = self.negamax(self.board, depth, 0, alpha, beta);
+            if self.aborted {
+                return None;
+            }
+            if score <= alpha && alpha > -INFINITY {
+                window *= 2;
+                alpha = (score - window).max(-INFINITY);
+            } else if score >= beta && beta < INFINITY {
+                window *= 2;
+                beta = (score + window).min(INFINITY);
+            } else {
+                return Some(score);
+            }
+        }
+    }
+
+    /// Returns the principal variation of the last search, following the best moves of the
+    /// transposition table for at most `depth` moves.
+    fn principal_variation(&mut self, first: Move, depth: u32) -> Vec<Move> {
+        let mut pv = vec![first];
+        let mut board = self.board.advance_state(first).expect("move is legal");
+        while (pv.len() as u32) < depth {
+            let Some(m) = self
+                .table
+                .get(board.zobrist_hash())
+                .and_then(|entry| entry.best_move)
+            else {
+                break;
+            };
+            // The entry can belong to another position with the same hash.
+            let Some(next) = board.advance_state(m) else {
+                break;
+            };
+            pv.push(m);
+            board = next;
+        }
+        pv
+    }
+}
+
+/// The transposition table and the history scores are kept across positions, the killer moves are
+/// not.
+impl<C: Clock> Engine for AlphaBetaEngine<C> {
+    fn set_position(&mut self, board: Board) {
+        self.board = board;
+        self.ordering.clear();
+    }
+
+    fn go(&mut self, limits: SearchLimits) -> SearchResult {
+        let start = self.clock.now();
+        self.deadline = limits
+            .time_ms
+            .map(|ms| start + Duration::from_millis(ms as u64));
+        self.max_nodes = limits.nodes;
+        self.nodes = 0;
+        self.can_abort = false;
+        self.aborted = false;
+
+        let mut best = None;
+        let mut score = None;
+        let mut completed_depth = None;
+        if self.board.winner().is_none() {
+            // The empty cells bound the number of moves left in the game.
+            let max_depth = limits
+                .depth
+                .unwrap_or(u32::MAX)
+                .min(self.board.empty_cells().count() as u32);
+            for depth in 1..=max_depth.max(1) {
+                self.root_move = None;
+                let Some(result) = self.search_root(depth, score) else {
+                    break;
+                };
+                best = self.root_move;
+                score = Some(result);
+                completed_depth = Some(depth);
+                self.can_abort = true;
+                if result.abs() >= WIN_THRESHOLD {
+                    // The game is decided, and deeper searches would not find a faster win.
+                    break;
+                }
+            }
+        }
+
+        SearchResult {
+            best_move: best,
+            win_rate: score.map(|score| match score {
+                score if score >= WIN_THRESHOLD => 1.0,
+                score if score <= -WIN_THRESHOLD => 0.0,
+                score => eval_to_win_rate(score),
+            }),
+            pv: best.map_or_else(Vec::new, |m| {
+                self.principal_variation(m, completed_depth.unwrap_or(1))
+            }),
+            nodes: self.nodes,
+            depth: completed_depth,
+            elapsed: self.clock.now() - start,
+        }
+    }
+
+    fn search_handle(&self) -> SearchHandle {
+        self.search_handle()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(notation: &str) -> SearchResult {
+        let board = Board::from_notation(notation).unwrap();
+        AlphaBetaEngine::for_position(board).go(SearchLimits {
+            depth: Some(4),
+            ..SearchLimits::default()
+        })
+    }
+
+    #[test]
+    fn finds_forced_win() {
+        // X has won the top left and top middle sub-boards and completes the top right one.
+        let result = search("XXXOO4/XXXOO4/XX1OO4/1OO6/9/9/9/9/9 X 2");
+        assert_eq!(result.best_move, Some(Move { major: 2, minor: 2 }));
+        assert_eq!(result.win_rate, Some(1.0));
+    }
+
+    #[test]
+    fn plays_only_move() {
+        // O threatens to win the top right sub-board and the game. Playing in the middle right
+        // cell sends O to a decided sub-board, from where they can play the winning move.
+        let result = search("OOOXX4/OOOXX4/OO1XO1OXX/9/9/XXX6/9/9/9 X 2");
+        assert_eq!(result.best_move, Some(Move { major: 2, minor: 2 }));
+    }
+}
@@ -0,0 +1,199 @@
+//! Opening book that learns from finished games.
+//!
+//! For every position of the first few plies, the book keeps a score for each move that was
+//! played from it, updated with the outcome of every recorded game. Older games are discounted, so
+//! the book follows the engine as it gets stronger instead of being built once.
+//!
+//! The text format has a header line followed by one line per position and move:
+//! `<position hash> <major>,<minor> <score> <games>`, where the position hash is
+//! [`Board::snapshot_hash`] in hex.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use rand::Rng;
+use uttt_core::{Board, Move, Outcome};
+
+use crate::{BookError, DecisionReport, DecisionSource, MoveSummary, DECISION_REPORT_RUNNERS_UP};
+
+/// Header of the text format of an [`OpeningBook`].
+const BOOK_HEADER: &str = "uttt-book 1";
+/// Number of plies of a game recorded in the book by default.
+pub const BOOK_DEFAULT_PLIES: usize = 8;
+/// Factor applied to the statistics of all moves of a position every time a game passes through
+/// it, so that a game counts less the older it is.
+pub const BOOK_DECAY: f32 = 0.98;
+
+/// Statistics of a move in an [`OpeningBook`], discounted by [`BOOK_DECAY`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BookEntry {
+    /// Discounted sum of the scores of the games, counting a win as `1` and a tie as `0.5` for the
+    /// player making the move.
+    pub score: f32,
+    /// Discounted number of games.
+    pub games: f32,
+}
+
+impl BookEntry {
+    /// Returns the mean score, assuming one win and one loss before any game was recorded so that
+    /// rarely played moves are not over- or underrated.
+    pub fn weight(self) -> f32 {
+        (self.score + 1.0) / (self.games + 2.0)
+    }
+}
+
+/// An opening book that improves as more games are recorded. See the [module
+/// documentation](self).
+#[derive(Clone, Default)]
+pub struct OpeningBook {
+    entries: BTreeMap<u64, Vec<(Move, BookEntry)>>,
+}
+
+impl OpeningBook {
+    /// Create an empty [`OpeningBook`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the moves of the position found in the book and their statistics.
+    pub fn moves(&self, board: &Board) -> &[(Move, BookEntry)] {
+        self.entries
+            .get(&board.snapshot_hash())
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the book move with the highest weight, if the position is in the book.
+    pub fn best_move(&self, board: &Board) -> Option<Move> {
+        self.moves(board)
+            .iter()
+            .max_by(|(_, a), (_, b)| a.weight().total_cmp(&b.weight()))
+            .map(|&(m, _)| m)
+    }
+
+    /// Picks a book move with a probability proportional to its weight, so that the book keeps
+    /// trying alternatives, if the position is in the book.
+    pub fn sample_move(&self, board: &Board, rng: &mut impl Rng) -> Option<Move> {
+        let moves = self.moves(board);
+        let total: f32 = moves.iter().map(|(_, entry)| entry.weight()).sum();
+        if moves.is_empty() {
+            return None;
+        }
+        let mut x = rng.gen_range(0.0..total);
+        for &(m, entry) in moves {
+            if x < entry.weight() {
+                return Some(m);
+            }
+            x -= entry.weight();
+        }
+        // Only reachable through rounding errors.
+        moves.last().map(|&(m, _)| m)
+    }
+
+    /// Summarizes why [`best_move`](Self::best_move) would be played, if the position is in the
+    /// book. Visits are the rounded discounted numbers of games and win rates are the weights of
+    /// the moves.
+    pub fn decision_report(&self, board: &Board) -> Option<DecisionReport> {
+        let mut moves: Vec<_> = self
+            .moves(board)
+            .iter()
+            .map(|&(m, entry)| MoveSummary {
+                m,
+                visits: entry.games.round() as u32,
+                win_rate: entry.weight(),
+                proven: None,
+            })
+            .collect();
+        let best = self.best_move(board)?;
+        let chosen = moves.remove(moves.iter().position(|summary| summary.m == best)?);
+        moves.sort_by(|a, b| b.win_rate.total_cmp(&a.win_rate));
+        moves.truncate(DECISION_REPORT_RUNNERS_UP);
+        Some(DecisionReport {
+            chosen,
+            runners_up: moves,
+            source: DecisionSource::Book,
+        })
+    }
+
+    /// Records the first `plies` moves of a finished game played from the initial position.
+    /// Moves after an illegal move are ignored.
+    pub fn record_game(&mut self, moves: &[Move], outcome: Outcome, plies: usize) {
+        let mut board = Board::new();
+        for &m in moves.iter().take(plies) {
+            let score = outcome.score_for(board.player_to_move);
+            let Some(next) = board.advance_state(m) else {
+                break;
+            };
+            let position = self.entries.entry(board.snapshot_hash()).or_default();
+            for (_, entry) in position.iter_mut() {
+                entry.score *= BOOK_DECAY;
+                entry.games *= BOOK_DECAY;
+            }
+            let index = match position.iter().position(|&(book_move, _)| book_move == m) {
+                Some(index) => index,
+                None => {
+                    position.push((m, BookEntry::default()));
+                    position.len() - 1
+                }
+            };
+            position[index].1.score += score;
+            position[index].1.games += 1.0;
+            board = next;
+        }
+    }
+
+    /// Returns the number of positions in the book.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the book does not contain any positions.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Display for OpeningBook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", BOOK_HEADER)?;
+        for (hash, moves) in &self.entries {
+            for (m, entry) in moves {
+                writeln!(
+                    f,
+                    "{:016x} {},{} {} {}",
+                    hash, m.major, m.minor, entry.score, entry.games
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for OpeningBook {
+    type Err = BookError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next() != Some(BOOK_HEADER) {
+            return Err(BookError::Invalid("unsupported header".to_string()));
+        }
+        let mut book = Self::new();
+        for line in lines {
+            let invalid = || BookError::Invalid(format!("invalid line `{}`", line));
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let [hash, m, score, games] = fields[..] else {
+                return Err(invalid());
+            };
+            let hash = u64::from_str_radix(hash, 16).map_err(|_| invalid())?;
+            let entry = BookEntry {
+                score: score.parse().map_err(|_| invalid())?,
+                games: games.parse().map_err(|_| invalid())?,
+            };
+            book.entries
+                .entry(hash)
+                .or_default()
+                .push((m.parse()?, entry));
+        }
+        Ok(book)
+    }
+}
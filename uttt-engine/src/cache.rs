@@ -0,0 +1,297 @@
+//! Bounded cache for position evaluations.
+
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Index used as a null link in the entry list.
+const NIL: usize = usize::MAX;
+/// Number of independently locked shards of a [`SharedEvalCache`].
+const SHARDS: usize = 16;
+
+/// How an [`EvalCache`] picks the entry to evict when it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplacementPolicy {
+    /// Evict the least recently used entry.
+    #[default]
+    Lru,
+    /// Evict the entry that was inserted first, regardless of how often it was used since.
+    Fifo,
+}
+
+/// Hasher for keys that already are uniformly distributed hashes.
+#[derive(Default)]
+struct IdentityHasher(u64);
+
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {
+        unreachable!("IdentityHasher only supports u64 keys")
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = n;
+    }
+}
+
+struct Entry<V> {
+    key: u64,
+    value: V,
+    /// Next entry towards the most recently used (or inserted) one.
+    prev: usize,
+    /// Next entry towards the least recently used (or inserted) one.
+    next: usize,
+}
+
+/// A bounded cache mapping position hashes (e.g. Zobrist hashes) to evaluations, so that
+/// positions that occur several times in the tree (or across moves) are only evaluated once.
+///
+/// Since the key is only a hash, different positions can collide. This is accepted for
+/// evaluations, which are only heuristics anyway.
+pub struct EvalCache<V> {
+    capacity: usize,
+    policy: ReplacementPolicy,
+    map: HashMap<u64, usize, BuildHasherDefault<IdentityHasher>>,
+    entries: Vec<Entry<V>>,
+    /// Most recently used (or inserted) entry.
+    head: usize,
+    /// Least recently used (or inserted) entry. This is the next entry to be evicted.
+    tail: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl<V> EvalCache<V> {
+    /// Create a new [`EvalCache`] holding at most `capacity` evaluations.
+    pub fn new(capacity: usize, policy: ReplacementPolicy) -> Self {
+        Self {
+            capacity,
+            policy,
+            map: HashMap::with_capacity_and_hasher(capacity, Default::default()),
+            entries: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the cached evaluation for the position hash, if any.
+    pub fn get(&mut self, key: u64) -> Option<&V> {
+        match self.map.get(&key) {
+            Some(&index) => {
+                self.hits += 1;
+                if self.policy == ReplacementPolicy::Lru {
+                    self.unlink(index);
+                    self.push_front(index);
+                }
+                Some(&self.entries[index].value)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Caches the evaluation for the position hash, evicting an entry if the cache is full.
+    pub fn insert(&mut self, key: u64, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some(&index) = self.map.get(&key) {
+            self.entries[index].value = value;
+            if self.policy == ReplacementPolicy::Lru {
+                self.unlink(index);
+                self.push_front(index);
+            }
+            return;
+        }
+
+        let index = if self.entries.len() < self.capacity {
+            self.entries.push(Entry {
+                key,
+                value,
+                prev: NIL,
+                next: NIL,
+            });
+            self.entries.len() - 1
+        } else {
+            // Reuse the slot of the evicted entry.
+            let index = self.tail;
+            self.unlink(index);
+            self.map.remove(&self.entries[index].key);
+            self.entries[index].key = key;
+            self.entries[index].value = value;
+            index
+        };
+        self.map.insert(key, index);
+        self.push_front(index);
+    }
+
+    /// Returns the cached evaluation for the position hash, computing and caching it with
+    /// `evaluate` if it is not cached yet.
+    pub fn get_or_insert_with(&mut self, key: u64, evaluate: impl FnOnce() -> V) -> V
+    where
+        V: Clone,
+    {
+        if let Some(value) = self.get(key) {
+            return value.clone();
+        }
+        let value = evaluate();
+        self.insert(key, value.clone());
+        value
+    }
+
+    /// Returns the number of cached evaluations.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the cache does not contain any evaluations.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the maximum number of cached evaluations.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the replacement policy of the cache.
+    pub fn policy(&self) -> ReplacementPolicy {
+        self.policy
+    }
+
+    /// Returns the number of lookups that found a cached evaluation.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of lookups that did not find a cached evaluation.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Removes all cached evaluations. Hit and miss counts are kept.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.entries.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let Entry { prev, next, .. } = self.entries[index];
+        match prev {
+            NIL => self.head = next,
+            prev => self.entries[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.entries[next].prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, index: usize) {
+        self.entries[index].prev = NIL;
+        self.entries[index].next = self.head;
+        match self.head {
+            NIL => self.tail = index,
+            head => self.entries[head].prev = index,
+        }
+        self.head = index;
+    }
+}
+
+/// A thread-safe [`EvalCache`] that can be shared between engines, e.g. by a server analyzing
+/// several games at once. Cloning is cheap and returns a handle to the same cache.
+///
+/// The cache is split into shards with their own lock and their own replacement order, so that
+/// engines on different threads rarely wait for each other.
+pub struct SharedEvalCache<V> {
+    shards: Arc<[Mutex<EvalCache<V>>]>,
+}
+
+impl<V> Clone for SharedEvalCache<V> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: Arc::clone(&self.shards),
+        }
+    }
+}
+
+impl<V: Clone> SharedEvalCache<V> {
+    /// Create a new [`SharedEvalCache`] holding roughly at most `capacity` evaluations.
+    pub fn new(capacity: usize, policy: ReplacementPolicy) -> Self {
+        let shard_capacity = capacity.div_ceil(SHARDS);
+        Self {
+            shards: (0..SHARDS)
+                .map(|_| Mutex::new(EvalCache::new(shard_capacity, policy)))
+                .collect(),
+        }
+    }
+
+    /// Returns the cached evaluation for the position hash, if any.
+    pub fn get(&self, key: u64) -> Option<V> {
+        self.shard(key).get(key).cloned()
+    }
+
+    /// Caches the evaluation for the position hash, evicting an entry if the cache is full.
+    pub fn insert(&self, key: u64, value: V) {
+        self.shard(key).insert(key, value);
+    }
+
+    /// Replaces the cached evaluation for the position hash with `update(old)`, where `old` is the
+    /// cached evaluation if any. Other threads cannot modify the entry in between.
+    pub fn update(&self, key: u64, update: impl FnOnce(Option<&V>) -> V) {
+        let mut shard = self.shard(key);
+        let value = update(shard.get(key));
+        shard.insert(key, value);
+    }
+
+    /// Returns the number of cached evaluations.
+    pub fn len(&self) -> usize {
+        self.shards().map(|shard| shard.len()).sum()
+    }
+
+    /// Returns `true` if the cache does not contain any evaluations.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of lookups that found a cached evaluation.
+    pub fn hits(&self) -> u64 {
+        self.shards().map(|shard| shard.hits()).sum()
+    }
+
+    /// Returns the number of lookups that did not find a cached evaluation.
+    pub fn misses(&self) -> u64 {
+        self.shards().map(|shard| shard.misses()).sum()
+    }
+
+    /// Removes all cached evaluations. Hit and miss counts are kept.
+    pub fn clear(&self) {
+        self.shards().for_each(|mut shard| shard.clear());
+    }
+
+    fn shard(&self, key: u64) -> MutexGuard<'_, EvalCache<V>> {
+        // The high bits of the key select the shard, so that the low bits, which the map of the
+        // shard uses to pick a bucket, stay spread over all of its buckets.
+        let index = (key >> 60) as usize % SHARDS;
+        lock(&self.shards[index])
+    }
+
+    fn shards(&self) -> impl Iterator<Item = MutexGuard<'_, EvalCache<V>>> {
+        self.shards.iter().map(lock)
+    }
+}
+
+/// Locks a shard. A panic while holding the lock cannot leave a shard in an inconsistent state
+/// that matters for a cache, so poisoning is ignored.
+fn lock<V>(shard: &Mutex<EvalCache<V>>) -> MutexGuard<'_, EvalCache<V>> {
+    shard.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,138 @@
+//! Time sources used for search time budgets.
+
+use std::cell::Cell;
+use std::time::Duration;
+
+use instant::Instant;
+
+/// A source of time. The engine only ever looks at differences between two readings, so the
+/// origin of the clock does not matter.
+pub trait Clock {
+    /// Returns the time elapsed since an arbitrary, fixed point in time.
+    fn now(&self) -> Duration;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+/// The default [`Clock`], backed by [`instant::Instant`]. This uses `performance.now()` on wasm and
+/// [`std::time::Instant`] everywhere else.
+#[derive(Debug, Clone, Copy)]
+pub struct InstantClock {
+    origin: Instant,
+}
+
+impl InstantClock {
+    /// Create a new [`InstantClock`] starting at the current time.
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for InstantClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for InstantClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// A [`Clock`] that only advances when told to, for testing time budget behavior.
+///
+/// The clock can either be advanced manually with [`advance`](Self::advance) or automatically by a
+/// fixed amount every time it is read, see [`with_tick`](Self::with_tick). Pass a reference to the
+/// engine to keep control over the clock while the engine is using it.
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: Cell<Duration>,
+    tick: Duration,
+}
+
+impl MockClock {
+    /// Create a new [`MockClock`] starting at zero that never advances on its own.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new [`MockClock`] starting at zero that advances by `tick` every time it is read.
+    pub fn with_tick(tick: Duration) -> Self {
+        Self {
+            now: Cell::new(Duration::ZERO),
+            tick,
+        }
+    }
+
+    /// Advances the clock by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Duration {
+        let now = self.now.get();
+        self.now.set(now + self.tick);
+        now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uttt_core::Board;
+
+    use super::*;
+    use crate::{AlphaBetaEngine, Engine, MctsEngine, SearchLimits};
+
+    const TICK: Duration = Duration::from_millis(1);
+    const BUDGET: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn mcts_stops_at_time_budget() {
+        let clock = MockClock::with_tick(TICK);
+        let mut mcts: MctsEngine<&MockClock> = MctsEngine::with_clock(Board::new(), &clock);
+        let (iterations, _) = mcts.run_search(BUDGET.as_millis());
+        assert!(iterations > 0);
+        // The search does not start an iteration that would end past the budget.
+        let end = clock.now();
+        assert!(end <= BUDGET, "search ran until {:?}", end);
+        assert!(end >= BUDGET - 10 * TICK, "search stopped at {:?}", end);
+    }
+
+    #[test]
+    fn mcts_go_reports_elapsed_time() {
+        let clock = MockClock::with_tick(TICK);
+        let mut mcts: MctsEngine<&MockClock> = MctsEngine::with_clock(Board::new(), &clock);
+        let result = mcts.go(SearchLimits {
+            time_ms: Some(BUDGET.as_millis()),
+            ..Default::default()
+        });
+        assert!(result.best_move.is_some());
+        assert!(result.elapsed >= BUDGET - 10 * TICK && result.elapsed <= BUDGET + 2 * TICK);
+        // The last reading of the search is the one before this one.
+        assert_eq!(result.elapsed + TICK, clock.now());
+    }
+
+    #[test]
+    fn alpha_beta_stops_at_time_budget() {
+        let clock = MockClock::with_tick(TICK);
+        let mut engine = AlphaBetaEngine::with_clock(Board::new(), &clock);
+        let result = engine.go(SearchLimits {
+            time_ms: Some(BUDGET.as_millis()),
+            ..Default::default()
+        });
+        assert!(result.best_move.is_some());
+        assert!(result.depth.is_some());
+        // The search is aborted at the first clock reading past the deadline.
+        assert!(result.elapsed >= BUDGET && result.elapsed <= BUDGET + 2 * TICK);
+        assert_eq!(result.elapsed + TICK, clock.now());
+    }
+}
@@ -0,0 +1,181 @@
+//! Parameters of the search of an [`MctsEngine`](crate::MctsEngine).
+
+use std::sync::Arc;
+
+use crate::{
+    HeuristicRollout, Puct, ReplacementPolicy, RolloutPolicy, SelectionPolicy, Ucb1, Ucb1Tuned,
+};
+
+/// Formula used to pick the child to descend into during selection.
+#[derive(Debug, Clone, Default)]
+pub enum SelectionFormula {
+    /// See [`Ucb1`].
+    #[default]
+    Ucb1,
+    /// See [`Ucb1Tuned`].
+    Ucb1Tuned,
+    /// See [`Puct`].
+    Puct,
+    /// A user-provided [`SelectionPolicy`].
+    Custom(Arc<dyn SelectionPolicy>),
+}
+
+impl SelectionFormula {
+    /// Returns the [`SelectionPolicy`] of the formula.
+    pub fn policy(&self) -> &dyn SelectionPolicy {
+        match self {
+            Self::Ucb1 => &Ucb1,
+            Self::Ucb1Tuned => &Ucb1Tuned,
+            Self::Puct => &Puct,
+            Self::Custom(policy) => policy.as_ref(),
+        }
+    }
+}
+
+/// Custom policies are equal if they are the same instance.
+impl PartialEq for SelectionFormula {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Custom(a), Self::Custom(b)) => Arc::ptr_eq(a, b),
+            (a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+        }
+    }
+}
+
+/// How rollouts pick the moves of the simulated games.
+///
+/// Searches on the GPU always play uniformly random moves.
+#[derive(Debug, Clone, Default)]
+pub enum Rollout {
+    /// Uniformly random legal moves.
+    #[default]
+    Random,
+    /// Winning and blocking moves first, see [`HeuristicRollout`]. Rollouts are slower, but
+    /// closer to real games.
+    Heuristic(HeuristicRollout),
+    /// A user-provided [`RolloutPolicy`].
+    Custom(Arc<dyn RolloutPolicy>),
+}
+
+/// Custom policies are equal if they are the same instance.
+impl PartialEq for Rollout {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Random, Self::Random) => true,
+            (Self::Heuristic(a), Self::Heuristic(b)) => a == b,
+            (Self::Custom(a), Self::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// What the search does once the tree has reached the size limit of [`MctsConfig::max_nodes`] or
+/// [`MctsConfig::max_memory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeLimitPolicy {
+    /// Stop expanding nodes. Iterations only play rollouts from the leaves of the tree.
+    #[default]
+    StopExpanding,
+    /// Discard the least visited nodes, keeping half of the limit, and keep expanding. The moves
+    /// of discarded nodes are expanded again if the search comes back to them.
+    Prune,
+}
+
+/// Parameters of the search of an [`MctsEngine`](crate::MctsEngine), see
+/// [`MctsEngine::with_config`](crate::MctsEngine::with_config). Fields that are not set can be
+/// taken from the [`Default`], which is what the other constructors use.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsConfig {
+    /// Weight of exploration against the win rate in the selection formula. Higher values spread
+    /// the visits over more moves. Defaults to `√2`.
+    pub exploration: f32,
+    pub selection: SelectionFormula,
+    /// Score of the moves of a node that were not expanded yet (first play urgency), compared
+    /// against the scores of its expanded children in selection. A partially expanded node only
+    /// expands a new move once none of its children scores higher, so that a strong move can be
+    /// searched deeper before its siblings were all tried once. Lower values make the search more
+    /// selective. If `None` (the default), unexpanded moves are always tried first.
+    pub first_play_urgency: Option<f32>,
+    pub rollout: Rollout,
+    /// Maximum number of moves played by a rollout. Rollouts that are cut short score the position
+    /// they reached with [`Board::evaluate`](uttt_core::Board::evaluate) instead of playing to the
+    /// end, so that more of them fit in the same time. Unlimited if `None` (the default).
+    pub rollout_depth: Option<u32>,
+    /// Number of evaluations of truncated rollouts kept in an [`EvalCache`](crate::EvalCache), so
+    /// that positions reached again, in the same search or after the root moved, are looked up
+    /// instead of evaluated. The cache lives as long as the engine. No cache if `None` (the
+    /// default).
+    pub eval_cache_size: Option<usize>,
+    /// Which evaluation the cache evicts once it holds `eval_cache_size` of them. Defaults to
+    /// [`ReplacementPolicy::Lru`].
+    pub eval_cache_policy: ReplacementPolicy,
+    /// Maximum number of nodes in the search tree. What happens once the tree is this big depends
+    /// on the `node_limit_policy`. Unlimited if `None` (the default).
+    pub max_nodes: Option<usize>,
+    /// Maximum size of the search tree in bytes, e.g. to stay within the memory of a browser tab.
+    /// This is turned into a number of nodes, counting the map of positions used for
+    /// `transpositions` and the memory needed to prune the tree or move its root, and the smaller
+    /// of this and `max_nodes` applies.
+    /// Unlimited if `None` (the default).
+    pub max_memory: Option<usize>,
+    pub node_limit_policy: NodeLimitPolicy,
+    /// Maximum depth below the root at which nodes are expanded. Unlimited if `None` (the
+    /// default).
+    pub max_depth: Option<u32>,
+    /// Weight of the Dirichlet noise mixed into the priors of the root moves, as in AlphaZero
+    /// self-play, so that the search also tries moves it would otherwise never consider and
+    /// self-play games do not all follow the same openings. New noise is sampled whenever the
+    /// root changes. Only has an effect with selection formulas that use priors, such as
+    /// [`Puct`]. `0.0` (the default) disables the noise.
+    pub root_noise_epsilon: f32,
+    /// Concentration of the root noise. Lower values concentrate the noise on fewer moves.
+    /// Defaults to `0.3`.
+    pub root_noise_alpha: f32,
+    /// Whether to prove the outcome of nodes from their children (MCTS-Solver). A node is won once
+    /// one of its moves wins, and decided once all of its moves are expanded and decided. Proven
+    /// nodes are not searched any further and moves proven to lose are never selected, so the
+    /// engine finds forced wins and avoids forced losses much sooner. Defaults to `true`.
+    pub solver: bool,
+    /// Whether nodes of the same position, reached through different orders of moves, share their
+    /// statistics. What is learned about a position through one order of moves then also guides
+    /// the search through the others, at the cost of a hash table lookup for every expanded node.
+    /// Defaults to `false`.
+    pub transpositions: bool,
+    /// Whether [`run_search`](crate::MctsEngine::run_search) stops before the end of its time
+    /// budget once the search is decided: when the root is proven or has a single move, when the
+    /// most visited move cannot be overtaken by the runner-up in the time left, or when it has at
+    /// least `early_stop_visit_share` of the visits. The time left is returned by
+    /// [`MctsEngine::saved_time`](crate::MctsEngine::saved_time). Defaults to `false`.
+    pub early_stop: bool,
+    /// Share of the visits of the root that makes its most visited move decided, see
+    /// `early_stop`. Defaults to `0.9`.
+    pub early_stop_visit_share: f32,
+    /// Seed of the random number generator, which makes searches with an iteration budget
+    /// deterministic. A random seed is picked if `None` (the default).
+    pub seed: Option<u64>,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            exploration: std::f32::consts::SQRT_2,
+            selection: SelectionFormula::default(),
+            first_play_urgency: None,
+            rollout: Rollout::default(),
+            rollout_depth: None,
+            eval_cache_size: None,
+            eval_cache_policy: ReplacementPolicy::default(),
+            max_nodes: None,
+            max_memory: None,
+            node_limit_policy: NodeLimitPolicy::default(),
+            max_depth: None,
+            root_noise_epsilon: 0.0,
+            root_noise_alpha: 0.3,
+            solver: true,
+            transpositions: false,
+            early_stop: false,
+            early_stop_visit_share: 0.9,
+            seed: None,
+        }
+    }
+}
@@ -0,0 +1,1902 @@
+//! MCTS algorithm.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use instant::Instant;
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
+
+use uttt_core::{Board, Move, MoveError, MoveList, MovesIter, Outcome, PackedMove, Player, Rules};
+
+use crate::{
+    Clock, DecisionReport, DecisionSource, Engine, EngineError, EvalCache, InstantClock,
+    MctsConfig, MoveEval, MoveSummary, NodeLimitPolicy, OpponentModel, Puct, ReproBundle,
+    ReproUnavailable, Rollout, RolloutPolicy, SearchBudget, SearchLimits, SearchResult,
+    SelectionFormula, SelectionPolicy, SelectionStats, SharedEvalCache, Ucb1, Ucb1Tuned,
+    DECISION_REPORT_RUNNERS_UP,
+};
+
+/// Value of the packed previous move for the root node, which has no previous move.
+const NO_MOVE: u8 = 0x7f;
+
+/// Returns the index of the `n`-th set bit of `mask`, counting from the least significant bit.
+fn nth_set_bit(mask: u128, n: u32) -> u32 {
+    let low = mask as u64;
+    let (mut word, mut n, offset) = if n < low.count_ones() {
+        (low, n, 0)
+    } else {
+        ((mask >> 64) as u64, n - low.count_ones(), 64)
+    };
+    while n > 0 {
+        // Clear the lowest set bit.
+        word &= word - 1;
+        n -= 1;
+    }
+    word.trailing_zeros() + offset
+}
+
+/// Samples the Gamma distribution with shape `alpha` and scale `1`, with the method of Marsaglia
+/// and Tsang.
+fn sample_gamma(rng: &mut impl Rng, alpha: f64) -> f64 {
+    if alpha < 1.0 {
+        // Boost the shape above 1 and scale the sample back down.
+        let u = 1.0 - rng.gen::<f64>();
+        return sample_gamma(rng, alpha + 1.0) * u.powf(1.0 / alpha);
+    }
+    let d = alpha - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        // Standard normal sample, with the Box-Muller transform.
+        let x = (-2.0 * (1.0 - rng.gen::<f64>()).ln()).sqrt()
+            * (std::f64::consts::TAU * rng.gen::<f64>()).cos();
+        let v = (1.0 + c * x).powi(3);
+        if v <= 0.0 {
+            continue;
+        }
+        let u = 1.0 - rng.gen::<f64>();
+        if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+            return d * v;
+        }
+    }
+}
+
+/// Index of a [`Node`] in the tree of an [`MctsEngine`].
+type NodeId = u32;
+/// [`NodeId`] used as a null link.
+const NIL: NodeId = NodeId::MAX;
+/// The root node is always the first node of the tree.
+const ROOT: NodeId = 0;
+
+/// Memory used by [`MctsEngine::rebuild_tree`] for every node of the tree, in bytes.
+const REBUILD_BYTES_PER_NODE: usize = 3 * std::mem::size_of::<NodeId>();
+
+/// Memory used by [`MctsEngine::positions`] for every node of the tree, in bytes. The map keeps
+/// at least an eighth of its buckets empty and rounds their number up to a power of two, and each
+/// bucket has a control byte.
+const POSITION_BYTES_PER_NODE: usize = (std::mem::size_of::<(u64, NodeId)>() + 1) * 16 / 7 + 1;
+
+/// Node in MCTS. Links to other nodes are indices into [`MctsEngine::nodes`].
+#[derive(Clone)]
+struct Node {
+    parent: NodeId,
+    /// The children of a node form a singly linked list, starting at `first_child` and continuing
+    /// with the `next_sibling` of each child. Children are only allocated when expanded.
+    first_child: NodeId,
+    next_sibling: NodeId,
+    /// The legal moves that have not been expanded yet, as a mask where bit `major * 9 + minor` is
+    /// set for every move.
+    untried: u128,
+    board: Board,
+
+    /// Node whose `wins`, `visits` and `proven` are the statistics of this node. This is the node
+    /// itself, unless [`MctsConfig::transpositions`] is set and its position was expanded before
+    /// through another sequence of moves, in which case it is the first node of the position.
+    stats: NodeId,
+    wins: f32,
+    /// Sum of the squares of the rollout scores added to `wins`.
+    squared_wins: f32,
+    visits: u32,
+    /// Prior probability of the move leading to this node, see [`SelectionPolicy::prior`].
+    prior: f32,
+    /// Outcome of the game with best play from this node, if it is terminal or proven by the
+    /// solver. See [`MctsConfig::solver`].
+    proven: Option<Outcome>,
+    /// The [`PackedMove`] that led to this node or [`NO_MOVE`] for the root.
+    packed_move: u8,
+}
+
+impl Node {
+    fn new(id: NodeId, parent: NodeId, board: Board, previous_move: Option<Move>) -> Self {
+        let proven = board.winner();
+        let untried = if proven.is_some() {
+            0
+        } else {
+            board.legal_move_mask()
+        };
+
+        Self {
+            parent,
+            first_child: NIL,
+            next_sibling: NIL,
+            untried,
+            board,
+            stats: id,
+            wins: 0.0,
+            squared_wins: 0.0,
+            visits: 0,
+            prior: 1.0,
+            proven,
+            packed_move: previous_move.map_or(NO_MOVE, |m| PackedMove::from(m).index()),
+        }
+    }
+
+    /// Returns the move that led to this node or `None` if this is the root node.
+    fn previous_move(&self) -> Option<Move> {
+        match self.packed_move {
+            NO_MOVE => None,
+            index => Some(PackedMove::from_index(index).into()),
+        }
+    }
+
+    fn is_fully_expanded(&self) -> bool {
+        self.untried == 0
+    }
+}
+
+/// Maps a [`Board::evaluate`] score to the probability of winning. A position this much better
+/// than another one for a player is won `e` times more often by them.
+const ROLLOUT_EVAL_SCALE: f32 = 200.0;
+
+/// Returns the probability of winning of the player a [`Board::evaluate`] score is for, see
+/// [`ROLLOUT_EVAL_SCALE`].
+pub(crate) fn eval_to_win_rate(eval: i32) -> f32 {
+    1.0 / (1.0 + f32::exp(-eval as f32 / ROLLOUT_EVAL_SCALE))
+}
+
+/// Choose moves with the [`MctsConfig::rollout`] policy starting from `board` until a terminal
+/// state is reached, or [`MctsConfig::rollout_depth`] moves were played.
+///
+/// Returns the score of the game for [`Player::X`] and the number of moves simulated. The score
+/// is `1.0` for a win, `0.5` for a tie and `0.0` for a loss. For a truncated rollout, it is the
+/// probability of X winning according to the static evaluation of the last position, looked up
+/// in `eval_cache` if there is one.
+fn rollout(
+    mut board: Board,
+    config: &MctsConfig,
+    eval_cache: Option<&mut EvalCache<i32>>,
+    rng: &mut impl Rng,
+) -> (f32, u32) {
+    // The loop already stops once the game is decided, so strict rules would only add overhead.
+    board.rules = Rules::default();
+    let mut moves_count = 0;
+    let mut moves = MoveList::new();
+    loop {
+        if let Some(outcome) = board.winner() {
+            return (outcome.score_for(Player::X), moves_count);
+        }
+        if config.rollout_depth == Some(moves_count) {
+            let eval = match eval_cache {
+                Some(cache) => {
+                    cache.get_or_insert_with(board.zobrist_hash(), || board.evaluate(Player::X))
+                }
+                None => board.evaluate(Player::X),
+            };
+            return (eval_to_win_rate(eval), moves_count);
+        }
+        board.generate_moves_into(&mut moves);
+        let m = match &config.rollout {
+            Rollout::Random => *moves.choose(rng).unwrap(),
+            Rollout::Heuristic(heuristic) => heuristic.pick(&board, &moves, rng),
+            Rollout::Custom(custom) => custom.pick(&board, &moves, rng),
+        };
+        // SAFETY: m is a valid Move.
+        board = unsafe { board.advance_state_unsafe(m) };
+        moves_count += 1;
+    }
+}
+
+/// Temperature of [`MctsEngine::human_move`] at strength `0.0`. A move whose win rate is this much
+/// lower than the best one is picked `e` times less often.
+const HUMAN_MAX_TEMPERATURE: f32 = 0.05;
+/// Moves with fewer visits than this fraction of the most visited move are never picked by
+/// [`MctsEngine::human_move`].
+const HUMAN_MIN_VISIT_FRACTION: f32 = 0.05;
+
+/// Default probability of [`MctsEngine::beginner_move`] to not pick the best move.
+pub const BEGINNER_MISTAKE_RATE: f32 = 0.3;
+/// [`MctsEngine::beginner_move`] only picks moves up to this rank, counting the best move as `1`.
+pub const BEGINNER_MAX_RANK: usize = 3;
+/// [`MctsEngine::beginner_move`] only picks moves whose win rate is at most this much lower than the
+/// win rate of the best move.
+pub const BEGINNER_MAX_VALUE_GAP: f32 = 0.1;
+
+/// Duration of the search performed by [`MctsEngine::calibrate`].
+const CALIBRATION_MS: u128 = 50;
+
+/// Search speed measured on the current device. See [`MctsEngine::calibrate`].
+///
+/// Difficulty levels should be expressed as a "strength" rather than a time budget, since the same
+/// time budget results in very different engines on slow and fast devices. A strength is the think
+/// time in milliseconds on a reference machine performing
+/// [`REFERENCE_ITERATIONS_PER_SEC`](Self::REFERENCE_ITERATIONS_PER_SEC) iterations per second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    /// Number of MCTS iterations performed per second on this device.
+    pub iterations_per_sec: f64,
+}
+
+impl Calibration {
+    /// Speed of the reference machine strengths are defined against.
+    pub const REFERENCE_ITERATIONS_PER_SEC: f64 = 100_000.0;
+
+    /// Returns the number of iterations corresponding to the given strength. This does not depend
+    /// on the current device.
+    pub fn iterations_for_strength(strength_ms: u128) -> u32 {
+        (strength_ms as f64 * Self::REFERENCE_ITERATIONS_PER_SEC / 1000.0) as u32
+    }
+
+    /// Returns the number of iterations this device performs within the time budget.
+    pub fn iterations_for_millis(&self, time_budget_ms: u128) -> u32 {
+        (time_budget_ms as f64 * self.iterations_per_sec / 1000.0) as u32
+    }
+
+    /// Returns the estimated time in milliseconds this device needs to perform `iterations`.
+    pub fn millis_for_iterations(&self, iterations: u32) -> u128 {
+        (iterations as f64 * 1000.0 / self.iterations_per_sec) as u128
+    }
+}
+
+/// Target time between two clock reads in [`MctsEngine::run_search`].
+const TIME_CHECK_PERIOD: Duration = Duration::from_micros(250);
+/// Maximum number of iterations between two clock reads in [`MctsEngine::run_search`].
+const MAX_TIME_CHECK_INTERVAL: u32 = 1024;
+
+/// Decides when [`MctsEngine::run_search`] reads the clock.
+///
+/// Reading the clock is surprisingly expensive on some targets (`performance.now()` on wasm), so
+/// it is only read every few iterations. The interval is adapted to the recent cost of an
+/// iteration so that the clock is read roughly every [`TIME_CHECK_PERIOD`], and never much later
+/// than the end of the time budget.
+struct TimeCheck {
+    interval: u32,
+    since_check: u32,
+    last_check: Duration,
+    /// Average duration of an iteration since the previous clock read.
+    per_iteration: Duration,
+}
+
+impl TimeCheck {
+    fn new(start: Duration) -> Self {
+        Self {
+            interval: 1,
+            since_check: 0,
+            last_check: start,
+            per_iteration: Duration::ZERO,
+        }
+    }
+
+    /// Counts an iteration. Returns `true` if the clock should be read now.
+    fn tick(&mut self) -> bool {
+        self.since_check += 1;
+        self.since_check >= self.interval
+    }
+
+    /// Records a clock reading and adapts the interval. `remaining` is the time left in the budget.
+    fn record(&mut self, now: Duration, remaining: Duration) {
+        let per_iteration = (now - self.last_check) / self.since_check;
+        self.per_iteration = per_iteration;
+        let target = TIME_CHECK_PERIOD.min(remaining);
+        self.interval = if per_iteration.is_zero() {
+            // Iterations are faster than the resolution of the clock.
+            self.interval.saturating_mul(2)
+        } else {
+            (target.as_nanos() / per_iteration.as_nanos()) as u32
+        }
+        .clamp(1, MAX_TIME_CHECK_INTERVAL);
+        self.since_check = 0;
+        self.last_check = now;
+    }
+}
+
+/// Weight of the [`OpponentModel`] bias in the tree policy, relative to the win rate.
+const OPPONENT_MODEL_WEIGHT: f32 = 0.1;
+
+/// Nodes with at least this many visits are added to the [`TranspositionTable`].
+const SHARED_MIN_VISITS: u32 = 64;
+/// Statistics found in the [`TranspositionTable`] count as at most this many visits of a newly
+/// expanded node.
+const SHARED_PRIOR_MAX_VISITS: u32 = 32;
+
+/// The root needs at least this many visits before [`MctsConfig::early_stop_visit_share`] applies,
+/// so that the visit shares are meaningful.
+const EARLY_STOP_MIN_VISITS: u32 = 1000;
+
+/// Win and visit counts of a position, from the point of view of the player who moved into it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionStats {
+    pub wins: f32,
+    pub visits: u32,
+}
+
+/// Table of [`PositionStats`] shared between engines, keyed by [`Board::zobrist_hash`]. See
+/// [`MctsEngine::set_transposition_table`].
+pub type TranspositionTable = SharedEvalCache<PositionStats>;
+
+/// Interval between two [`SearchInfo`] updates sent by [`MctsEngine::run_search_streaming`].
+const SEARCH_INFO_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress of a running search, see [`MctsEngine::run_search_streaming`].
+#[derive(Debug, Clone, Copy)]
+pub struct SearchInfo {
+    /// Time since the search was started.
+    pub elapsed: Duration,
+    /// Number of iterations performed by this search.
+    pub iterations: u32,
+    /// Number of moves simulated by this search.
+    pub moves: u32,
+    /// Number of nodes in the search tree.
+    pub nodes: usize,
+    /// Best move so far, i.e. the most visited root move.
+    pub best_move: Option<Move>,
+    /// Win rate of the best move for the player to move.
+    pub win_rate: Option<f32>,
+}
+
+/// Callback registered with [`MctsEngine::set_progress_callback`].
+struct ProgressCallback {
+    interval: u32,
+    callback: Box<dyn FnMut(&SearchInfo) + Send>,
+}
+
+/// Stops the searches of an engine from another thread, e.g. when the user of a GUI cancels the
+/// search. See [`Engine::search_handle`].
+#[derive(Debug, Clone)]
+pub struct SearchHandle {
+    pub(crate) stop: Arc<AtomicBool>,
+}
+
+impl SearchHandle {
+    /// Stops the running search of the engine as soon as possible. The search returns normally,
+    /// so the best move so far is still available. If no search is running, the next search stops
+    /// right after it starts.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Monte Carlo tree search engine. The search tree is stored in a single [`Vec`], so the engine
+/// owns all of its state and can be moved between threads.
+///
+/// All randomness comes from a seeded RNG of type `R`, so searches with the same seed, position and
+/// number of iterations build the same tree. See [`ReproBundle`].
+pub struct MctsEngine<C: Clock = InstantClock, R: Rng = StdRng> {
+    nodes: Vec<Node>,
+    clock: C,
+    rng: R,
+    /// Seed `rng` was seeded with when the tree was created.
+    seed: u64,
+    /// Number of iterations performed since the tree was created.
+    iterations: u32,
+    /// Why the tree cannot be rebuilt from `seed` and `iterations`, if it cannot.
+    unreplayable: Option<ReproUnavailable>,
+    record_repro: bool,
+    repro: Result<ReproBundle, ReproUnavailable>,
+    table: Option<TranspositionTable>,
+    /// The node holding the statistics of every position in the tree, keyed by
+    /// [`Board::zobrist_hash`]. Only filled if [`MctsConfig::transpositions`] is set.
+    positions: HashMap<u64, NodeId>,
+    /// Time kept free at the end of every time budget.
+    time_margin: Duration,
+    /// Time on `clock` that no search may run past.
+    deadline: Option<Duration>,
+    opponent_model: Option<OpponentModel>,
+    config: MctsConfig,
+    /// See [`MctsConfig::eval_cache_size`]. Kept across positions, since entries are keyed by
+    /// [`Board::zobrist_hash`].
+    eval_cache: Option<EvalCache<i32>>,
+    /// Dirichlet noise mixed into the priors of the root moves, by [`PackedMove`] index. Empty if
+    /// [`MctsConfig::root_noise_epsilon`] is zero.
+    root_noise: Vec<f32>,
+    /// Set by [`SearchHandle::stop`].
+    stop: Arc<AtomicBool>,
+    progress: Option<ProgressCallback>,
+    /// Time left in the budget of the last [`run_search`](Self::run_search) when it stopped
+    /// early.
+    saved_time: Duration,
+}
+
+impl MctsEngine {
+    /// Create a new [`MctsEngine`] searching the given position.
+    pub fn for_position(board: Board) -> Self {
+        Self::with_clock(board, InstantClock::new())
+    }
+
+    /// Create a new [`MctsEngine`] searching the given position with a fixed seed, which makes
+    /// searches with an iteration budget deterministic.
+    pub fn with_seed(board: Board, seed: u64) -> Self {
+        Self::with_config(
+            board,
+            MctsConfig {
+                seed: Some(seed),
+                ..MctsConfig::default()
+            },
+        )
+    }
+
+    /// Create a new [`MctsEngine`] searching the given position with the given parameters.
+    pub fn with_config(board: Board, config: MctsConfig) -> Self {
+        Self::with_clock_and_config(board, InstantClock::new(), config)
+    }
+
+    /// Measures the search speed of the current device by running a short search from the
+    /// initial position. This takes about 50ms.
+    pub fn calibrate() -> Calibration {
+        let mut mcts = MctsEngine::for_position(Board::new());
+
+        let start = Instant::now();
+        let mut iters = 0u32;
+        while start.elapsed().as_millis() < CALIBRATION_MS {
+            mcts.iterate();
+            iters += 1;
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+        Calibration {
+            iterations_per_sec: iters as f64 / elapsed,
+        }
+    }
+}
+
+impl<R: Rng + SeedableRng + 'static> MctsEngine<InstantClock, R> {
+    /// Create a new [`MctsEngine`] searching the given position that takes all of its randomness
+    /// from `rng`, e.g. a seeded `SmallRng`, which is faster than the default [`StdRng`].
+    ///
+    /// The engine has no seed, so its searches are not recorded in a [`ReproBundle`] until the
+    /// position is set again.
+    pub fn with_rng(board: Board, rng: R) -> Self {
+        let mut mcts =
+            Self::with_clock_and_config(board, InstantClock::new(), MctsConfig::default());
+        mcts.rng = rng;
+        mcts.seed = 0;
+        mcts.unreplayable = Some(ReproUnavailable::Unseeded);
+        mcts
+    }
+}
+
+impl<C: Clock, R: Rng + SeedableRng + 'static> MctsEngine<C, R> {
+    /// Create a new [`MctsEngine`] searching the given position that measures time budgets with
+    /// the given [`Clock`].
+    pub fn with_clock(board: Board, clock: C) -> Self {
+        Self::with_clock_and_config(board, clock, MctsConfig::default())
+    }
+
+    /// Create a new [`MctsEngine`] searching the given position with the given [`Clock`] and
+    /// parameters.
+    pub fn with_clock_and_config(board: Board, clock: C, config: MctsConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(|| thread_rng().gen());
+        let mut mcts = Self {
+            nodes: vec![Node::new(ROOT, NIL, board, None)],
+            clock,
+            rng: R::seed_from_u64(seed),
+            seed,
+            iterations: 0,
+            unreplayable: None,
+            record_repro: false,
+            repro: Err(ReproUnavailable::Disabled),
+            table: None,
+            positions: HashMap::new(),
+            time_margin: Duration::ZERO,
+            deadline: None,
+            opponent_model: None,
+            eval_cache: config
+                .eval_cache_size
+                .map(|size| EvalCache::new(size, config.eval_cache_policy)),
+            config,
+            root_noise: Vec::new(),
+            stop: Arc::new(AtomicBool::new(false)),
+            progress: None,
+            saved_time: Duration::ZERO,
+        };
+        mcts.sample_root_noise();
+        mcts
+    }
+
+    /// Returns a handle that stops the searches of this engine from another thread. All handles of
+    /// an engine are the same, so the handle can be taken before the engine is moved to the
+    /// search thread, e.g. by [`run_search_streaming`](Self::run_search_streaming).
+    pub fn search_handle(&self) -> SearchHandle {
+        SearchHandle {
+            stop: self.stop.clone(),
+        }
+    }
+
+    /// Calls `callback` with the progress of the search every `interval` iterations of every
+    /// search, e.g. to show it live in a frontend. Replaces the previous callback.
+    ///
+    /// The callback runs on the thread of the search, between two iterations, so it should return
+    /// quickly.
+    ///
+    /// # Panics
+    /// This method panics if `interval` is `0`.
+    pub fn set_progress_callback(
+        &mut self,
+        interval: u32,
+        callback: impl FnMut(&SearchInfo) + Send + 'static,
+    ) {
+        assert!(interval > 0, "progress interval must be positive");
+        self.progress = Some(ProgressCallback {
+            interval,
+            callback: Box::new(callback),
+        });
+    }
+
+    /// Removes the callback set by [`set_progress_callback`](Self::set_progress_callback).
+    pub fn clear_progress_callback(&mut self) {
+        self.progress = None;
+    }
+
+    /// Calls the progress callback if the engine just performed a multiple of its interval of
+    /// iterations. `start` is the time on the clock when the search started.
+    fn report_progress(&mut self, start: Duration, iterations: u32, moves: u32) {
+        let Some(mut progress) = self
+            .progress
+            .take_if(|progress| self.iterations.is_multiple_of(progress.interval))
+        else {
+            return;
+        };
+        let info = self.search_info(SearchInfo {
+            elapsed: self.clock.now() - start,
+            iterations,
+            moves,
+            nodes: 0,
+            best_move: None,
+            win_rate: None,
+        });
+        (progress.callback)(&info);
+        self.progress = Some(progress);
+    }
+
+    /// Returns the part of the time budget of the last [`run_search`](Self::run_search) that was
+    /// left unused because the search was decided, see [`MctsConfig::early_stop`]. A time manager
+    /// can add it to the budget of later moves. Zero if the last search used its whole budget.
+    pub fn saved_time(&self) -> Duration {
+        self.saved_time
+    }
+
+    /// Returns `true` if the search is decided: the root is proven or has a single move, or its
+    /// most visited move cannot be overtaken by the runner-up in `remaining_iterations` or has an
+    /// overwhelming share of the visits. See [`MctsConfig::early_stop`].
+    fn is_decided(&self, remaining_iterations: u64) -> bool {
+        if self.stats(ROOT).proven.is_some() || self.position().count_moves() == 1 {
+            return true;
+        }
+        let loss = Outcome::from(self.position().player_to_move.opponent());
+        let (mut best, mut runner_up) = (0, 0);
+        for child in self.children(ROOT) {
+            let stats = self.stats(child);
+            if stats.proven == Some(loss) {
+                continue;
+            }
+            if stats.visits > best {
+                runner_up = best;
+                best = stats.visits;
+            } else if stats.visits > runner_up {
+                runner_up = stats.visits;
+            }
+        }
+        let visits = self.stats(ROOT).visits;
+        (best - runner_up) as u64 > remaining_iterations
+            || (visits >= EARLY_STOP_MIN_VISITS
+                && best as f32 >= self.config.early_stop_visit_share * visits as f32)
+    }
+
+    /// Returns `true` and resets the request if the search was asked to stop.
+    fn take_stop(&self) -> bool {
+        self.stop.load(Ordering::Relaxed) && self.stop.swap(false, Ordering::Relaxed)
+    }
+
+    /// Returns the parameters of the search.
+    pub fn config(&self) -> &MctsConfig {
+        &self.config
+    }
+
+    /// Returns the cache of rollout evaluations, e.g. to check its hit rate, or `None` if
+    /// [`MctsConfig::eval_cache_size`] is not set.
+    pub fn eval_cache(&self) -> Option<&EvalCache<i32>> {
+        self.eval_cache.as_ref()
+    }
+
+    /// Returns the seed of the current search tree, or `0` for an engine created with
+    /// [`with_rng`](MctsEngine::with_rng) whose position was not set since.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the number of iterations performed since the tree was created, over all searches.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// Enables or disables recording a [`ReproBundle`] after every search. Disabled by default.
+    pub fn set_record_repro(&mut self, record: bool) {
+        self.record_repro = record;
+    }
+
+    /// Returns the [`ReproBundle`] of the last search, or why none was recorded. Searches performed
+    /// on the GPU, with a [`TranspositionTable`], with an [`OpponentModel`], with custom policies,
+    /// on a reused tree or with an RNG other than [`StdRng`] cannot be recorded.
+    pub fn repro(&self) -> Result<&ReproBundle, ReproUnavailable> {
+        self.repro.as_ref().map_err(|reason| *reason)
+    }
+
+    /// Returns why the current search tree cannot be recorded in a [`ReproBundle`], if it cannot.
+    fn repro_unavailable(&self) -> Option<ReproUnavailable> {
+        let custom_policy = matches!(self.config.selection, SelectionFormula::Custom(_))
+            || matches!(self.config.rollout, Rollout::Custom(_));
+        if !self.record_repro {
+            Some(ReproUnavailable::Disabled)
+        } else if let Some(reason) = self.unreplayable {
+            Some(reason)
+        } else if TypeId::of::<R>() != TypeId::of::<StdRng>() {
+            Some(ReproUnavailable::Unseeded)
+        } else if custom_policy {
+            Some(ReproUnavailable::CustomPolicy)
+        } else if self.table.is_some() {
+            Some(ReproUnavailable::TranspositionTable)
+        } else if self.opponent_model.is_some() {
+            Some(ReproUnavailable::OpponentModel)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the [`TranspositionTable`] shared with other engines, or stops sharing if `None`.
+    ///
+    /// Newly expanded nodes start with the statistics of their position found in the table, and
+    /// the statistics of well searched nodes are added to the table after every search. Searches
+    /// using a table are not reproducible, since its contents depend on the other engines.
+    pub fn set_transposition_table(&mut self, table: Option<TranspositionTable>) {
+        self.table = table;
+    }
+
+    /// Sets the model of the opponent, or assumes that the opponent plays the best replies if
+    /// `None` (the default).
+    ///
+    /// At nodes where the opponent is to move, the tree policy favors the moves the model
+    /// predicts, so the engine spends more time on the replies the opponent actually plays. The
+    /// bias fades as the moves get visited more often. Searches using a model are not recorded in
+    /// a [`ReproBundle`], since the model is not part of it.
+    pub fn set_opponent_model(&mut self, model: Option<OpponentModel>) {
+        self.opponent_model = model;
+    }
+
+    /// Adds the statistics of well searched nodes to the transposition table, if any. Existing
+    /// entries are only replaced by statistics with more visits.
+    fn publish_stats(&self) {
+        let Some(table) = &self.table else {
+            return;
+        };
+        for (id, node) in self.nodes.iter().enumerate() {
+            // Nodes sharing the statistics of another node have none of their own.
+            if node.stats != id as NodeId || node.visits < SHARED_MIN_VISITS {
+                continue;
+            }
+            let stats = PositionStats {
+                wins: node.wins,
+                visits: node.visits,
+            };
+            table.update(node.board.zobrist_hash(), |old| match old {
+                Some(old) if old.visits >= stats.visits => *old,
+                _ => stats,
+            });
+        }
+    }
+
+    /// Shares the statistics of the search that just finished and records a [`ReproBundle`] for
+    /// it, if enabled.
+    fn finish_search(&mut self, budget: SearchBudget) {
+        self.publish_stats();
+        self.repro = match self.repro_unavailable() {
+            Some(reason) => Err(reason),
+            None => Ok(ReproBundle {
+                seed: self.seed,
+                config: MctsConfig {
+                    seed: None,
+                    ..self.config.clone()
+                },
+                position: self.position(),
+                budget,
+                iterations: self.iterations,
+                best_move: self.best_move().ok(),
+            }),
+        };
+    }
+
+    /// Returns the [`Clock`] used for time budgets.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Sets the time [`run_search`](Self::run_search) keeps free at the end of its time budget,
+    /// e.g. for sending the move over the network. Zero by default.
+    pub fn set_time_margin(&mut self, margin: Duration) {
+        self.time_margin = margin;
+    }
+
+    /// Sets a hard deadline, as a reading of the engine's [`clock`](Self::clock), that
+    /// [`run_search`](Self::run_search) never runs past, whatever its time budget. The time margin
+    /// also applies to the deadline.
+    pub fn set_deadline(&mut self, deadline: Option<Duration>) {
+        self.deadline = deadline;
+    }
+
+    /// Returns the position being searched.
+    pub fn position(&self) -> Board {
+        self.nodes[ROOT as usize].board
+    }
+
+    /// Discards the search tree and starts searching the given position instead. The new tree is
+    /// seeded from the RNG of the engine.
+    pub fn set_position(&mut self, board: Board) {
+        self.nodes.clear();
+        self.nodes.push(Node::new(ROOT, NIL, board, None));
+        self.positions.clear();
+        self.seed = self.rng.gen();
+        self.rng = R::seed_from_u64(self.seed);
+        self.iterations = 0;
+        self.unreplayable = None;
+        self.repro = Err(ReproUnavailable::NoSearch);
+        self.sample_root_noise();
+    }
+
+    /// Plays `m` from the searched position, by either player, and keeps searching the position
+    /// after it. The subtree of `m` becomes the new tree, so the statistics gathered for it are
+    /// kept; the rest of the tree is discarded. If `m` was not expanded yet, this is the same as
+    /// [`set_position`](Self::set_position).
+    ///
+    /// A reused tree cannot be rebuilt from a seed, so no [`ReproBundle`] is recorded until the
+    /// position is set again.
+    pub fn advance_root(&mut self, m: Move) -> Result<(), MoveError> {
+        let next = self.position().try_advance(m)?;
+        let Some(child) = self
+            .children(ROOT)
+            .find(|&child| self.node(child).previous_move() == Some(m))
+        else {
+            self.set_position(next);
+            return Ok(());
+        };
+
+        self.rebuild_tree(child, |_, _| true);
+        self.unreplayable = Some(ReproUnavailable::ReusedTree);
+        self.repro = Err(ReproUnavailable::ReusedTree);
+        self.sample_root_noise();
+        Ok(())
+    }
+
+    /// Samples new Dirichlet noise for the moves of the root if
+    /// [`MctsConfig::root_noise_epsilon`] is set, and mixes it into the priors of the root moves
+    /// that are already expanded.
+    fn sample_root_noise(&mut self) {
+        self.root_noise.clear();
+        if self.config.root_noise_epsilon <= 0.0 {
+            return;
+        }
+        let mut noise = vec![0.0; 81];
+        let mask = self.node(ROOT).untried | self.children_mask(ROOT);
+        let alpha = self.config.root_noise_alpha as f64;
+        let mut total = 0.0;
+        for m in MovesIter::from_mask(mask) {
+            let sample = sample_gamma(&mut self.rng, alpha);
+            noise[PackedMove::from(m).index() as usize] = sample;
+            total += sample;
+        }
+        if total == 0.0 {
+            // Every sample underflowed, which only happens with tiny alphas.
+            return;
+        }
+        self.root_noise = noise.into_iter().map(|x| (x / total) as f32).collect();
+
+        let children: Vec<_> = self.children(ROOT).collect();
+        for child in children {
+            let node = self.node(child);
+            let m = node
+                .previous_move()
+                .expect("child node has a previous move");
+            let prior = self.config.selection.policy().prior(&self.position(), m);
+            self.node_mut(child).prior = self.root_prior(prior, m);
+        }
+    }
+
+    /// Mixes the root noise into `prior`, the prior of the root move `m`.
+    fn root_prior(&self, prior: f32, m: Move) -> f32 {
+        match self.root_noise.get(PackedMove::from(m).index() as usize) {
+            Some(&noise) => {
+                let epsilon = self.config.root_noise_epsilon;
+                (1.0 - epsilon) * prior + epsilon * noise
+            }
+            None => prior,
+        }
+    }
+
+    /// Returns the moves of the expanded children of the node, as a mask like [`Node::untried`].
+    fn children_mask(&self, id: NodeId) -> u128 {
+        self.children(id)
+            .fold(0, |mask, child| mask | 1 << self.node(child).packed_move)
+    }
+
+    /// Replaces the tree with the subtree of `root`, leaving out every node for which `keep`
+    /// returns `false` along with its subtree. The moves of the nodes left out can be expanded
+    /// again.
+    ///
+    /// The subtree is renumbered breadth-first, so that the children of every node stay
+    /// contiguous, and moved in place, so the arena keeps its capacity and no second copy of the
+    /// tree is made. Besides the arena, this needs [`REBUILD_BYTES_PER_NODE`] for every node.
+    fn rebuild_tree(&mut self, old_root: NodeId, keep: impl Fn(&Self, NodeId) -> bool) {
+        let len = self.nodes.len();
+        // The new id of every kept node by its old id, and the old id of every kept node by its
+        // new id.
+        let mut new_ids = vec![NIL; len];
+        let mut old_ids = Vec::with_capacity(len);
+        new_ids[old_root as usize] = 0;
+        old_ids.push(old_root);
+        let mut id = 0;
+        while id < old_ids.len() {
+            for old_child in self.children(old_ids[id]) {
+                if keep(self, old_child) {
+                    new_ids[old_child as usize] = old_ids.len() as NodeId;
+                    old_ids.push(old_child);
+                }
+            }
+            id += 1;
+        }
+
+        // Relink the kept nodes with their new ids. The new node holding the statistics of each
+        // kept position, by the old one. The first kept node of a position takes over its
+        // statistics, since the old node holding them may not be kept.
+        let mut new_stats = vec![NIL; len];
+        self.positions.clear();
+        let root = self.node_mut(old_root);
+        root.parent = NIL;
+        root.next_sibling = NIL;
+        root.packed_move = NO_MOVE;
+        for (id, &old_id) in old_ids.iter().enumerate() {
+            let id = id as NodeId;
+            let old_stats = self.node(old_id).stats;
+            if new_stats[old_stats as usize] == NIL {
+                new_stats[old_stats as usize] = id;
+                let old = self.node(old_stats);
+                let (wins, squared_wins, visits, proven) =
+                    (old.wins, old.squared_wins, old.visits, old.proven);
+                let node = self.node_mut(old_id);
+                node.wins = wins;
+                node.squared_wins = squared_wins;
+                node.visits = visits;
+                node.proven = proven;
+                if self.config.transpositions {
+                    let hash = self.node(old_id).board.zobrist_hash();
+                    self.positions.insert(hash, id);
+                }
+            }
+            self.node_mut(old_id).stats = new_stats[old_stats as usize];
+
+            let mut old_child = self.node(old_id).first_child;
+            let mut first_child = NIL;
+            let mut previous = NIL;
+            while old_child != NIL {
+                let next = self.node(old_child).next_sibling;
+                let new_child = new_ids[old_child as usize];
+                if new_child == NIL {
+                    let packed_move = self.node(old_child).packed_move;
+                    self.node_mut(old_id).untried |= 1 << packed_move;
+                } else {
+                    match previous {
+                        NIL => first_child = new_child,
+                        previous => self.node_mut(previous).next_sibling = new_child,
+                    }
+                    self.node_mut(old_child).parent = id;
+                    previous = old_child;
+                }
+                old_child = next;
+            }
+            if previous != NIL {
+                self.node_mut(previous).next_sibling = NIL;
+            }
+            self.node_mut(old_id).first_child = first_child;
+        }
+
+        // Move every kept node to its new id. Each swap puts one node in its place.
+        for id in 0..len {
+            while new_ids[id] != NIL && new_ids[id] as usize != id {
+                let new_id = new_ids[id] as usize;
+                self.nodes.swap(id, new_id);
+                new_ids.swap(id, new_id);
+            }
+        }
+        self.nodes.truncate(old_ids.len());
+    }
+
+    /// Returns the maximum number of nodes of the tree, from [`MctsConfig::max_nodes`] and
+    /// [`MctsConfig::max_memory`].
+    fn node_limit(&self) -> Option<usize> {
+        match (self.config.max_nodes, self.memory_node_limit()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (limit, None) | (None, limit) => limit,
+        }
+    }
+
+    /// Returns the number of nodes that fit in [`MctsConfig::max_memory`], counting the arena,
+    /// the map of positions and the memory used while rebuilding the tree.
+    fn memory_node_limit(&self) -> Option<usize> {
+        let mut bytes_per_node = std::mem::size_of::<Node>() + REBUILD_BYTES_PER_NODE;
+        if self.config.transpositions {
+            bytes_per_node += POSITION_BYTES_PER_NODE;
+        }
+        self.config.max_memory.map(|bytes| bytes / bytes_per_node)
+    }
+
+    /// Makes room for one more node. The arena and the map of positions grow as usual, but never
+    /// past the number of nodes that fit in [`MctsConfig::max_memory`].
+    fn reserve_node(&mut self) {
+        let Some(limit) = self.memory_node_limit() else {
+            return;
+        };
+        let len = self.nodes.len();
+        if len == self.nodes.capacity() {
+            self.nodes
+                .reserve_exact((2 * len).min(limit).max(len + 1) - len);
+        }
+        let len = self.positions.len();
+        if self.config.transpositions && len == self.positions.capacity() {
+            self.positions
+                .reserve((2 * len).min(limit).max(len + 1) - len);
+        }
+    }
+
+    /// Prunes the least visited nodes if the tree is full and [`MctsConfig::node_limit_policy`]
+    /// is [`NodeLimitPolicy::Prune`], so that at most half of the limit is left.
+    fn prune_if_full(&mut self) {
+        let Some(limit) = self.node_limit() else {
+            return;
+        };
+        if self.config.node_limit_policy != NodeLimitPolicy::Prune
+            || self.nodes.len() < limit.max(2)
+        {
+            return;
+        }
+        let target = (limit / 2).max(1);
+        let mut visits: Vec<_> = (1..self.nodes.len() as NodeId)
+            .map(|id| self.stats(id).visits)
+            .collect();
+        // Keep the nodes with more visits than the `target`-th most visited one, besides the root.
+        let index = (target - 1).min(visits.len() - 1);
+        let threshold = *visits
+            .select_nth_unstable_by_key(index, |&visits| std::cmp::Reverse(visits))
+            .1;
+        self.rebuild_tree(ROOT, |engine, id| engine.stats(id).visits > threshold);
+    }
+
+    fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id as usize]
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id as usize]
+    }
+
+    /// Returns the node holding the statistics of the node, see [`Node::stats`].
+    fn stats(&self, id: NodeId) -> &Node {
+        self.node(self.node(id).stats)
+    }
+
+    fn stats_mut(&mut self, id: NodeId) -> &mut Node {
+        self.node_mut(self.node(id).stats)
+    }
+
+    /// Returns an iterator over the expanded children of the node.
+    fn children(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let link = |id: NodeId| (id != NIL).then_some(id);
+        std::iter::successors(link(self.node(id).first_child), move |&child| {
+            link(self.node(child).next_sibling)
+        })
+    }
+
+    /// Expand the node with a random move that has not been expanded yet. Returns the expanded
+    /// node.
+    ///
+    /// # Panics
+    /// This method panics if the node is already fully expanded.
+    fn expand(&mut self, id: NodeId) -> NodeId {
+        self.reserve_node();
+        let child_id = self.nodes.len() as NodeId;
+        let node = &mut self.nodes[id as usize];
+        assert!(node.untried != 0, "node cannot be fully expanded");
+
+        // Pick a random untried move.
+        let index = nth_set_bit(
+            node.untried,
+            self.rng.gen_range(0..node.untried.count_ones()),
+        );
+        node.untried &= !(1 << index);
+        let m = PackedMove::from_index(index as u8).into();
+
+        // Expand node.
+        // SAFETY: m is a valid Move.
+        let next = unsafe { node.board.advance_state_unsafe(m) };
+        let mut child = Node::new(child_id, id, next, Some(m));
+        child.next_sibling = node.first_child;
+        child.prior = self.config.selection.policy().prior(&node.board, m);
+        if id == ROOT {
+            child.prior = self.root_prior(child.prior, m);
+        }
+        if self.config.transpositions {
+            // Share the statistics of the position if it is already in the tree.
+            child.stats = *self
+                .positions
+                .entry(next.zobrist_hash())
+                .or_insert(child_id);
+        }
+        if child.stats == child_id {
+            if let Some(stats) = self.table.as_ref().and_then(|t| t.get(next.zobrist_hash())) {
+                // Scale the shared statistics down so that this search can still overturn them.
+                let visits = stats.visits.min(SHARED_PRIOR_MAX_VISITS);
+                child.wins = stats.wins * visits as f32 / stats.visits as f32;
+                child.visits = visits;
+            }
+        }
+        self.node_mut(id).first_child = child_id;
+        self.nodes.push(child);
+        child_id
+    }
+
+    /// Adds a rollout with `score` for [`Player::X`] to the statistics of the node and its
+    /// ancestors.
+    fn back_propagate(&mut self, id: NodeId, score: f32) {
+        // Walk up the node tree and increment parent visit/win count.
+        let mut next = id;
+        while next != NIL {
+            let stats = self.stats_mut(next);
+            // The wins of a node count for the player who made the move leading to it.
+            let win = match stats.board.player_to_move {
+                Player::X => 1.0 - score,
+                Player::O => score,
+            };
+            stats.wins += win;
+            stats.squared_wins += win * win;
+            stats.visits += 1;
+            next = self.node(next).parent;
+        }
+    }
+
+    /// Returns the child to descend into with the [`MctsConfig::selection`] formula and its score,
+    /// or `None` if every child is proven to lose.
+    fn select_best_child(&self, id: NodeId) -> Option<(NodeId, f32)> {
+        match &self.config.selection {
+            SelectionFormula::Ucb1 => self.select_best_child_with(id, &Ucb1),
+            SelectionFormula::Ucb1Tuned => self.select_best_child_with(id, &Ucb1Tuned),
+            SelectionFormula::Puct => self.select_best_child_with(id, &Puct),
+            SelectionFormula::Custom(policy) => self.select_best_child_with(id, policy.as_ref()),
+        }
+    }
+
+    /// Returns the statistics of the node for a [`SelectionPolicy`].
+    fn selection_stats(&self, id: NodeId) -> SelectionStats {
+        let stats = self.stats(id);
+        SelectionStats {
+            visits: stats.visits,
+            wins: stats.wins,
+            squared_wins: stats.squared_wins,
+            prior: self.node(id).prior,
+        }
+    }
+
+    fn select_best_child_with<P: SelectionPolicy + ?Sized>(
+        &self,
+        id: NodeId,
+        policy: &P,
+    ) -> Option<(NodeId, f32)> {
+        let parent = self.selection_stats(id);
+        let board = &self.node(id).board;
+        let opponent_model = self
+            .opponent_model
+            .as_ref()
+            .filter(|model| model.player() == board.player_to_move);
+        let exploration = self.config.exploration;
+        let loss = Outcome::from(board.player_to_move.opponent());
+        let mut best = None;
+        let mut best_score = f32::MIN;
+        for child in self.children(id) {
+            if self.stats(child).proven == Some(loss) {
+                continue;
+            }
+            let stats = self.selection_stats(child);
+            let v = stats.visits;
+            let mut score = policy.score(&parent, &stats, exploration);
+            if let Some(model) = opponent_model {
+                let m = self
+                    .node(child)
+                    .previous_move()
+                    .expect("child has a previous move");
+                score += OPPONENT_MODEL_WEIGHT * (model.move_weight(board, m) - 1.0)
+                    / f32::sqrt(v as f32);
+            }
+            if score > best_score {
+                best = Some((child, score));
+                best_score = score;
+            }
+        }
+        best
+    }
+
+    /// Proves the outcome of the ancestors of a node that was just proven, as far up as the
+    /// expanded part of the tree allows.
+    fn propagate_proof(&mut self, mut id: NodeId) {
+        loop {
+            id = self.node(id).parent;
+            if id == NIL || self.stats(id).proven.is_some() {
+                return;
+            }
+            match self.solve(id, |engine, child| engine.stats(child).proven) {
+                Some(outcome) => self.stats_mut(id).proven = Some(outcome),
+                None => return,
+            }
+        }
+    }
+
+    /// Walks down the tree from the root, picking the best child until a node that is terminal,
+    /// proven, or whose unexpanded moves are more urgent than its children is reached. Returns the
+    /// node and its depth below the root.
+    fn traverse(&self) -> (NodeId, u32) {
+        let mut id = ROOT;
+        let mut depth = 0;
+        while self.stats(id).proven.is_none() {
+            // The score of the unexpanded moves of the node.
+            let urgency = match self.config.first_play_urgency {
+                _ if self.node(id).is_fully_expanded() => f32::NEG_INFINITY,
+                Some(urgency) => urgency,
+                None => break,
+            };
+            match self.select_best_child(id) {
+                Some((child, score)) if score > urgency => id = child,
+                _ => break,
+            }
+            depth += 1;
+        }
+        (id, depth)
+    }
+
+    /// Returns `true` if the node at `depth` below the root that [`traverse`](Self::traverse)
+    /// returned can be expanded within the limits of the [`MctsConfig`].
+    fn can_expand(&self, id: NodeId, depth: u32) -> bool {
+        !self.node(id).is_fully_expanded()
+            && self.config.max_depth.is_none_or(|max| depth < max)
+            && self.node_limit().is_none_or(|max| self.nodes.len() < max)
+    }
+
+    /// Runs a single MCTS iteration. Returns whether a new node was expanded and the number of
+    /// moves simulated during the rollout.
+    fn iterate(&mut self) -> (bool, u32) {
+        self.iterations += 1;
+        self.prune_if_full();
+        // Phase 1: selection
+        let (node, depth) = self.traverse();
+        if let Some(outcome) = self.stats(node).proven {
+            // The result of the rollout is already known.
+            self.back_propagate(node, outcome.score_for(Player::X));
+            return (false, 0);
+        }
+        if !self.can_expand(node, depth) {
+            let (score, moves_count) = rollout(
+                self.node(node).board,
+                &self.config,
+                self.eval_cache.as_mut(),
+                &mut self.rng,
+            );
+            self.back_propagate(node, score);
+            return (false, moves_count);
+        }
+        // Phase 2: expansion
+        let expanded = self.expand(node);
+        // Phase 3: rollout
+        let (score, moves_count) = rollout(
+            self.node(expanded).board,
+            &self.config,
+            self.eval_cache.as_mut(),
+            &mut self.rng,
+        );
+        // Phase 4: back-propagation
+        self.back_propagate(expanded, score);
+        if self.config.solver && self.stats(expanded).proven.is_some() {
+            self.propagate_proof(expanded);
+        }
+        (true, moves_count)
+    }
+
+    /// Runs MCTS search. Returns the number of iterations performed and moves simulated.
+    ///
+    /// The search stops early when the next iteration would likely end past the time budget, the
+    /// [time margin](Self::set_time_margin) or the [deadline](Self::set_deadline), when it is
+    /// stopped through a [`SearchHandle`], or when the search is decided if
+    /// [`MctsConfig::early_stop`] is set.
+    ///
+    /// The tree is kept between calls, so calling this again continues the same search with the
+    /// statistics gathered so far. A GUI can search in short slices, updating between them, until
+    /// it is satisfied with the result.
+    pub fn run_search(&mut self, time_budget_ms: u128) -> (u32, u32) {
+        self.run_search_with(time_budget_ms, |_, _| {})
+    }
+
+    /// Same as [`run_search`](Self::run_search), but runs the search on a new thread and sends a
+    /// [`SearchInfo`] every 100ms and once more when the search is done. The engine is returned
+    /// through the [`JoinHandle`] once the search is done.
+    ///
+    /// Dropping the receiver does not stop the search.
+    pub fn run_search_streaming(
+        mut self,
+        time_budget_ms: u128,
+    ) -> (JoinHandle<Self>, Receiver<SearchInfo>)
+    where
+        C: Send + 'static,
+        R: Send,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            let start = self.clock.now();
+            let mut last_sent = Duration::ZERO;
+            let (iterations, moves) = self.run_search_with(time_budget_ms, |engine, info| {
+                if info.elapsed - last_sent >= SEARCH_INFO_INTERVAL {
+                    last_sent = info.elapsed;
+                    let _ = sender.send(engine.search_info(info));
+                }
+            });
+            let info = SearchInfo {
+                elapsed: self.clock.now() - start,
+                iterations,
+                moves,
+                nodes: 0,
+                best_move: None,
+                win_rate: None,
+            };
+            let _ = sender.send(self.search_info(info));
+            self
+        });
+        (handle, receiver)
+    }
+
+    /// Fills in the number of nodes, the best move and its win rate.
+    fn search_info(&self, info: SearchInfo) -> SearchInfo {
+        let best = self.best_child(ROOT).map(|child| self.stats(child));
+        SearchInfo {
+            nodes: self.nodes.len(),
+            best_move: self.best_child(ROOT).map(|child| {
+                self.node(child)
+                    .previous_move()
+                    .expect("child node has a previous move")
+            }),
+            win_rate: best.map(|stats| stats.wins / stats.visits.max(1) as f32),
+            ..info
+        }
+    }
+
+    /// Runs MCTS search, calling `on_progress` with the progress of the search whenever the clock
+    /// is read. The number of nodes, best move and win rate of the progress are not filled in.
+    fn run_search_with(
+        &mut self,
+        time_budget_ms: u128,
+        mut on_progress: impl FnMut(&Self, SearchInfo),
+    ) -> (u32, u32) {
+        let start = self.clock.now();
+        let mut budget = Duration::from_millis(time_budget_ms as u64);
+        if let Some(deadline) = self.deadline {
+            budget = budget.min(deadline.saturating_sub(start));
+        }
+        let budget = budget.saturating_sub(self.time_margin);
+        let mut time_check = TimeCheck::new(start);
+        self.saved_time = Duration::ZERO;
+
+        let mut iters = 0;
+        let mut moves = 0;
+        if budget.is_zero() {
+            self.finish_search(SearchBudget::Millis(time_budget_ms));
+            return (iters, moves);
+        }
+        loop {
+            let (expanded, moves_count) = self.iterate();
+            moves += moves_count;
+            if expanded {
+                iters += 1;
+            }
+            self.report_progress(start, iters, moves);
+            if self.take_stop() {
+                break;
+            }
+
+            if time_check.tick() {
+                let now = self.clock.now();
+                let elapsed = now - start;
+                // Stop if the next iteration would likely end past the budget.
+                if elapsed + time_check.per_iteration >= budget {
+                    break;
+                }
+                if self.config.early_stop {
+                    let remaining = budget - elapsed;
+                    let remaining_iterations = match time_check.per_iteration.as_nanos() {
+                        0 => u64::MAX,
+                        nanos => (remaining.as_nanos() / nanos) as u64,
+                    };
+                    if self.is_decided(remaining_iterations) {
+                        self.saved_time = remaining;
+                        break;
+                    }
+                }
+                time_check.record(now, budget - elapsed);
+                let info = SearchInfo {
+                    elapsed,
+                    iterations: iters,
+                    moves,
+                    nodes: 0,
+                    best_move: None,
+                    win_rate: None,
+                };
+                on_progress(self, info);
+            }
+        }
+        self.finish_search(SearchBudget::Millis(time_budget_ms));
+        (iters, moves)
+    }
+
+    /// Runs MCTS search for a fixed number of iterations instead of a time budget. Returns the
+    /// number of nodes expanded and moves simulated.
+    ///
+    /// Unlike [`run_search`](Self::run_search), the amount of work done does not depend on the
+    /// speed of the machine, which makes this suitable for benchmarking. The search still stops
+    /// early when it is stopped through a [`SearchHandle`]. Like `run_search`, repeated calls
+    /// continue the same search.
+    pub fn run_iterations(&mut self, iterations: u32) -> (u32, u32) {
+        let start = self.clock.now();
+        let mut iters = 0;
+        let mut moves = 0;
+        for _ in 0..iterations {
+            let (expanded, moves_count) = self.iterate();
+            moves += moves_count;
+            if expanded {
+                iters += 1;
+            }
+            self.report_progress(start, iters, moves);
+            if self.take_stop() {
+                break;
+            }
+        }
+        self.finish_search(SearchBudget::Iterations(iterations));
+        (iters, moves)
+    }
+
+    /// Runs MCTS search with rollouts performed on the GPU. Each round selects and expands up to
+    /// `batch_size` leaves, plays them all out in a single GPU dispatch and then back-propagates
+    /// the results. Returns the number of iterations performed and moves simulated.
+    ///
+    /// Because the statistics are only updated once per batch, the tree is explored less
+    /// selectively than with [`run_search`](Self::run_search) for the same number of rollouts.
+    #[cfg(feature = "gpu")]
+    pub fn run_search_gpu(
+        &mut self,
+        gpu: &crate::GpuRollouts,
+        time_budget_ms: u128,
+        batch_size: usize,
+    ) -> Result<(u32, u32), crate::GpuError> {
+        let start = self.clock.now();
+        self.unreplayable = Some(ReproUnavailable::Gpu);
+        self.repro = Err(ReproUnavailable::Gpu);
+
+        let mut iters = 0;
+        let mut moves = 0;
+        let mut leaves = Vec::with_capacity(batch_size);
+        let mut batch = crate::BoardBatch::new();
+        while (self.clock.now() - start).as_millis() < time_budget_ms {
+            self.prune_if_full();
+            // Phase 1 and 2: selection and expansion for the whole batch.
+            for _ in 0..batch_size {
+                let (node, depth) = self.traverse();
+                if let Some(outcome) = self.stats(node).proven {
+                    self.back_propagate(node, outcome.score_for(Player::X));
+                    continue;
+                }
+                let leaf = if !self.can_expand(node, depth) {
+                    node
+                } else {
+                    iters += 1;
+                    let expanded = self.expand(node);
+                    if self.config.solver && self.stats(expanded).proven.is_some() {
+                        self.propagate_proof(expanded);
+                    }
+                    expanded
+                };
+                batch.push(&self.node(leaf).board);
+                leaves.push(leaf);
+            }
+            // Phase 3: rollout
+            let results = gpu.rollout(&batch, self.rng.gen())?;
+            // Phase 4: back-propagation
+            for (leaf, (outcome, moves_count)) in leaves.drain(..).zip(results) {
+                moves += moves_count;
+                self.back_propagate(leaf, outcome.score_for(Player::X));
+                self.iterations += 1;
+                self.report_progress(start, iters, moves);
+            }
+            batch.clear();
+            if self.take_stop() {
+                break;
+            }
+        }
+        Ok((iters, moves))
+    }
+
+    /// Returns the most visited move from the root. A move that is proven to win is preferred,
+    /// and moves that are proven to lose are only returned if every move loses. If no move was
+    /// searched yet, any legal move is returned.
+    pub fn best_move(&self) -> Result<Move, EngineError> {
+        self.best_move_with_stats().map(|eval| eval.mv)
+    }
+
+    /// Same as [`best_move`](Self::best_move), but also returns the statistics of the move and the
+    /// line of play the engine expects after it.
+    ///
+    /// If no move was searched yet, the move has no visits and a win rate of `0.5`.
+    pub fn best_move_with_stats(&self) -> Result<MoveEval, EngineError> {
+        let root = self.node(ROOT);
+        match self.best_child(ROOT) {
+            Some(child) => Ok(self.move_eval(child)),
+            None if !root.is_fully_expanded() => {
+                let mv = PackedMove::from_index(root.untried.trailing_zeros() as u8).into();
+                Ok(MoveEval {
+                    mv,
+                    visits: 0,
+                    win_rate: 0.5,
+                    pv: vec![mv],
+                })
+            }
+            None => Err(EngineError::NoLegalMoves),
+        }
+    }
+
+    /// Returns the [`MoveEval`] of the move leading to the child.
+    fn move_eval(&self, child: NodeId) -> MoveEval {
+        let stats = self.stats(child);
+        let mut pv = Vec::new();
+        let mut next = Some(child);
+        while let Some(id) = next {
+            pv.push(
+                self.node(id)
+                    .previous_move()
+                    .expect("child node has a previous move"),
+            );
+            next = self.best_child(id);
+        }
+        MoveEval {
+            mv: pv[0],
+            visits: stats.visits,
+            win_rate: stats.wins / stats.visits.max(1) as f32,
+            pv,
+        }
+    }
+
+    /// Returns the up to `n` best moves from the root, best first, in the order of
+    /// [`best_move`](Self::best_move): proven wins, then moves that are not proven to lose, then
+    /// the rest, each by visits. Each move comes with its own principal variation.
+    ///
+    /// Only searched moves are returned, so the result is empty if the root was not searched yet.
+    pub fn top_moves(&self, n: usize) -> Vec<MoveEval> {
+        let mut children: Vec<_> = self.children(ROOT).collect();
+        // Of children that rank the same, `best_child` picks the last one.
+        children.reverse();
+        children.sort_by_key(|&child| std::cmp::Reverse(self.child_rank(ROOT, child)));
+        children
+            .into_iter()
+            .take(n)
+            .map(|child| self.move_eval(child))
+            .collect()
+    }
+
+    /// Returns the child of the node that [`best_move`](Self::best_move) picks, or `None` if the
+    /// node has no expanded children.
+    fn best_child(&self, id: NodeId) -> Option<NodeId> {
+        self.children(id)
+            .max_by_key(|&child| self.child_rank(id, child))
+    }
+
+    /// Returns a key that orders the children of node `id` from worst to best: by whether they are
+    /// proven to lose, not proven or proven to win for the player to move, and then by visits.
+    fn child_rank(&self, id: NodeId, child: NodeId) -> (u8, u32) {
+        let player = self.node(id).board.player_to_move;
+        let stats = self.stats(child);
+        let rank = match stats.proven.map(Outcome::player) {
+            Some(Some(winner)) if winner == player => 2,
+            Some(Some(_)) => 0,
+            _ => 1,
+        };
+        (rank, stats.visits)
+    }
+
+    /// Returns the move, visit count and win rate (from the point of view of the player to move)
+    /// of every expanded root move, most visited first.
+    fn root_moves(&self) -> Vec<(Move, u32, f32)> {
+        let mut moves: Vec<_> = self
+            .children(ROOT)
+            .filter(|&child| self.stats(child).visits > 0)
+            .map(|child| {
+                let m = self
+                    .node(child)
+                    .previous_move()
+                    .expect("child node has a previous move");
+                let stats = self.stats(child);
+                (m, stats.visits, stats.wins / stats.visits as f32)
+            })
+            .collect();
+        moves.sort_by_key(|&(_, visits, _)| std::cmp::Reverse(visits));
+        moves
+    }
+
+    /// Returns the outcome of the game with best play from the node, if the expanded part of the
+    /// tree proves it.
+    fn proven_outcome(&self, id: NodeId) -> Option<Outcome> {
+        match self.stats(id).proven {
+            Some(outcome) => Some(outcome),
+            None => self.solve(id, Self::proven_outcome),
+        }
+    }
+
+    /// Returns the outcome of the game with best play from a non-terminal node, given the
+    /// `proven` outcome of its children, if they prove it: a child won by the player to move, or
+    /// every move expanded and proven.
+    fn solve(
+        &self,
+        id: NodeId,
+        proven: impl Fn(&Self, NodeId) -> Option<Outcome>,
+    ) -> Option<Outcome> {
+        let node = self.node(id);
+        let win = Outcome::from(node.board.player_to_move);
+        let mut all_proven = node.is_fully_expanded();
+        let mut best = None;
+        for child in self.children(id) {
+            match proven(self, child) {
+                Some(outcome) if outcome == win => return Some(win),
+                Some(Outcome::Tie) => best = Some(Outcome::Tie),
+                Some(outcome) => {
+                    best.get_or_insert(outcome);
+                }
+                None => all_proven = false,
+            }
+        }
+        best.filter(|_| all_proven)
+    }
+
+    /// Summarizes why [`best_move`](Self::best_move) was chosen: how it compares to the
+    /// runners-up, and whether it is forced or proven by the searched tree. This walks the whole
+    /// tree, so it should be called once per move rather than during the search.
+    pub fn decision_report(&self) -> Result<DecisionReport, EngineError> {
+        let best = self.best_move()?;
+        let root = self.node(ROOT);
+        let loss = Outcome::from(root.board.player_to_move.opponent());
+
+        let mut moves: Vec<_> = self
+            .children(ROOT)
+            .map(|child| {
+                let stats = self.stats(child);
+                MoveSummary {
+                    m: self
+                        .node(child)
+                        .previous_move()
+                        .expect("child node has a previous move"),
+                    visits: stats.visits,
+                    win_rate: stats.wins / stats.visits.max(1) as f32,
+                    proven: self.proven_outcome(child),
+                }
+            })
+            .collect();
+        moves.sort_by_key(|summary| std::cmp::Reverse(summary.visits));
+        let chosen = match moves.iter().position(|summary| summary.m == best) {
+            Some(index) => moves.remove(index),
+            // The root was not searched yet.
+            None => MoveSummary {
+                m: best,
+                visits: 0,
+                win_rate: 0.0,
+                proven: None,
+            },
+        };
+
+        let source = if root.board.count_moves() == 1 {
+            DecisionSource::OnlyMove
+        } else if chosen.proven.is_some_and(|outcome| outcome != loss)
+            && chosen.proven == self.proven_outcome(ROOT)
+        {
+            DecisionSource::Proven
+        } else if root.is_fully_expanded()
+            && chosen.proven != Some(loss)
+            && moves.iter().all(|summary| summary.proven == Some(loss))
+        {
+            DecisionSource::Forced
+        } else {
+            DecisionSource::Search
+        };
+        moves.truncate(DECISION_REPORT_RUNNERS_UP);
+        Ok(DecisionReport {
+            chosen,
+            runners_up: moves,
+            source,
+        })
+    }
+
+    /// Picks a plausible move instead of always the best one, for more varied play against casual
+    /// opponents.
+    ///
+    /// Root moves are sampled with a probability that falls off exponentially with how much worse
+    /// their win rate is than the best one. `strength` ranges from `0.0` to `1.0`: lower strengths
+    /// make worse moves more likely, and a strength of `1.0` always returns
+    /// [`best_move`](Self::best_move). Moves that were barely searched are never picked, since
+    /// their win rates are mostly noise.
+    pub fn human_move(&mut self, strength: f32) -> Result<Move, EngineError> {
+        let temperature = HUMAN_MAX_TEMPERATURE * (1.0 - strength.clamp(0.0, 1.0));
+        let best = self.best_move()?;
+        let moves = self.root_moves();
+        let Some(&(_, max_visits, _)) = moves.first() else {
+            return Ok(best);
+        };
+        if temperature == 0.0 {
+            return Ok(best);
+        }
+
+        // Only well searched moves are candidates.
+        let candidates: Vec<_> = moves
+            .into_iter()
+            .filter(|&(_, visits, _)| visits as f32 >= HUMAN_MIN_VISIT_FRACTION * max_visits as f32)
+            .collect();
+        let best_value = candidates
+            .iter()
+            .map(|&(_, _, value)| value)
+            .fold(f32::MIN, f32::max);
+        let weights: Vec<_> = candidates
+            .iter()
+            .map(|&(_, _, value)| ((value - best_value) / temperature).exp())
+            .collect();
+
+        let mut x = self.rng.gen_range(0.0..weights.iter().sum::<f32>());
+        for (&(m, _, _), weight) in candidates.iter().zip(weights) {
+            if x < weight {
+                return Ok(m);
+            }
+            x -= weight;
+        }
+        // Only reachable through rounding errors.
+        Ok(best)
+    }
+
+    /// Samples a root move with a probability proportional to its visit count raised to the power
+    /// of `1 / temperature`, as in AlphaZero self-play. A temperature of `1.0` follows the visit
+    /// distribution, higher temperatures flatten it and lower ones sharpen it. A temperature of
+    /// `0.0` (or less) returns [`best_move`](Self::best_move).
+    pub fn sample_move(&mut self, temperature: f32) -> Result<Move, EngineError> {
+        let best = self.best_move()?;
+        let moves = self.root_moves();
+        let Some(&(_, max_visits, _)) = moves.first() else {
+            return Ok(best);
+        };
+        if temperature <= 0.0 {
+            return Ok(best);
+        }
+
+        // Relative to the most visited move, so that low temperatures do not overflow.
+        let weights: Vec<_> = moves
+            .iter()
+            .map(|&(_, visits, _)| (visits as f32 / max_visits as f32).powf(1.0 / temperature))
+            .collect();
+        let mut x = self.rng.gen_range(0.0..weights.iter().sum::<f32>());
+        for (&(m, _, _), weight) in moves.iter().zip(weights) {
+            if x < weight {
+                return Ok(m);
+            }
+            x -= weight;
+        }
+        // Only reachable through rounding errors.
+        Ok(best)
+    }
+
+    /// Picks the best move, except that with probability `mistake_rate` one of the next
+    /// [`BEGINNER_MAX_RANK`] - 1 moves is picked instead. Only moves whose win rate is at most
+    /// [`BEGINNER_MAX_VALUE_GAP`] below the best move are considered, so the engine makes small
+    /// mistakes that a beginner can exploit instead of blunders.
+    ///
+    /// [`BEGINNER_MISTAKE_RATE`] is a reasonable default for `mistake_rate`.
+    pub fn beginner_move(&mut self, mistake_rate: f32) -> Result<Move, EngineError> {
+        let best = self.best_move()?;
+        if !self.rng.gen_bool(mistake_rate.clamp(0.0, 1.0) as f64) {
+            return Ok(best);
+        }
+
+        let moves = self.root_moves();
+        let Some(&(_, _, best_value)) = moves.first() else {
+            return Ok(best);
+        };
+        let mistakes: Vec<_> = moves
+            .iter()
+            .take(BEGINNER_MAX_RANK)
+            .skip(1)
+            .filter(|&&(_, _, value)| best_value - value <= BEGINNER_MAX_VALUE_GAP)
+            .map(|&(m, _, _)| m)
+            .collect();
+        Ok(mistakes.choose(&mut self.rng).copied().unwrap_or(best))
+    }
+}
+
+impl Default for MctsEngine {
+    fn default() -> Self {
+        Self::for_position(Board::new())
+    }
+}
+
+/// A node limit counts iterations. Combined with a time limit, it is only checked whenever the
+/// clock is read, so the search may run a few iterations past it.
+impl<C: Clock, R: Rng + SeedableRng + 'static> Engine for MctsEngine<C, R> {
+    fn set_position(&mut self, board: Board) {
+        MctsEngine::set_position(self, board);
+    }
+
+    fn go(&mut self, limits: SearchLimits) -> SearchResult {
+        let start = self.clock.now();
+        let start_iterations = self.iterations;
+        if self.position().winner().is_none() {
+            let max_iterations = limits.nodes.map(|nodes| nodes.min(u32::MAX as u64) as u32);
+            match (limits.time_ms, max_iterations) {
+                (None, Some(iterations)) => {
+                    self.run_iterations(iterations);
+                }
+                (time_ms, max_iterations) => {
+                    // Search until stopped if there is no time limit.
+                    let time_ms = time_ms.unwrap_or(u64::MAX as u128);
+                    self.run_search_with(time_ms, |engine, _| {
+                        let iterations = engine.iterations.wrapping_sub(start_iterations);
+                        if max_iterations.is_some_and(|max| iterations >= max) {
+                            engine.stop.store(true, Ordering::Relaxed);
+                        }
+                    });
+                }
+            }
+        }
+        let eval = self.best_move_with_stats().ok();
+        SearchResult {
+            best_move: eval.as_ref().map(|eval| eval.mv),
+            win_rate: eval.as_ref().map(|eval| eval.win_rate),
+            pv: eval.map_or_else(Vec::new, |eval| eval.pv),
+            nodes: self.iterations.wrapping_sub(start_iterations) as u64,
+            depth: None,
+            elapsed: self.clock.now() - start,
+        }
+    }
+
+    fn search_handle(&self) -> SearchHandle {
+        self.search_handle()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eval_cache_serves_truncated_rollouts() {
+        let config = MctsConfig {
+            rollout_depth: Some(0),
+            seed: Some(1),
+            ..MctsConfig::default()
+        };
+        let mut uncached = MctsEngine::with_config(Board::new(), config.clone());
+        uncached.run_iterations(2000);
+        assert!(uncached.eval_cache().is_none());
+
+        let mut cached = MctsEngine::with_config(
+            Board::new(),
+            MctsConfig {
+                eval_cache_size: Some(1 << 12),
+                ..config
+            },
+        );
+        cached.run_iterations(2000);
+        // Cached evaluations are the same, so the search is too.
+        assert_eq!(cached.best_move(), uncached.best_move());
+        assert_eq!(
+            cached.best_move_with_stats().unwrap().win_rate,
+            uncached.best_move_with_stats().unwrap().win_rate
+        );
+        let cache = cached.eval_cache().unwrap();
+        assert!(cache.len() <= 1 << 12);
+
+        // The cache is kept for the next search, which evaluates many of the same positions.
+        let hits = cache.hits();
+        cached.set_position(Board::new());
+        cached.run_iterations(2000);
+        assert!(cached.eval_cache().unwrap().hits() > hits + 100);
+    }
+
+    /// Checks the links of the tree and the map of positions.
+    fn assert_consistent(mcts: &MctsEngine) {
+        assert_eq!(mcts.node(ROOT).parent, NIL);
+        for id in 0..mcts.nodes.len() as NodeId {
+            let node = mcts.node(id);
+            assert_eq!(node.untried & mcts.children_mask(id), 0);
+            for child in mcts.children(id) {
+                assert_eq!(mcts.node(child).parent, id);
+            }
+            assert_eq!(mcts.stats(id).stats, node.stats);
+            if mcts.config.transpositions {
+                assert_eq!(mcts.positions[&node.board.zobrist_hash()], node.stats);
+            }
+        }
+    }
+
+    #[test]
+    fn prune_stays_within_max_memory() {
+        let max_memory = 1 << 18;
+        let config = MctsConfig {
+            max_memory: Some(max_memory),
+            node_limit_policy: NodeLimitPolicy::Prune,
+            transpositions: true,
+            seed: Some(3),
+            ..MctsConfig::default()
+        };
+        let mut mcts = MctsEngine::with_config(Board::new(), config);
+        let limit = mcts.node_limit().unwrap();
+
+        // Enough iterations to fill the tree several times.
+        mcts.run_iterations(4 * limit as u32);
+        assert!(mcts.nodes.len() <= limit);
+        assert_eq!(mcts.node(ROOT).visits, 4 * limit as u32);
+        assert_consistent(&mcts);
+
+        let m = mcts.best_move().unwrap();
+        mcts.advance_root(m).unwrap();
+        mcts.run_iterations(limit as u32);
+        assert_consistent(&mcts);
+
+        // The memory allocated for the arena and the map, and the memory used by the rebuilds.
+        let capacity = mcts.nodes.capacity();
+        assert!(capacity <= limit);
+        let buckets = (mcts.positions.capacity() * 8 / 7).next_power_of_two();
+        let map_bytes = buckets * (std::mem::size_of::<(u64, NodeId)>() + 1);
+        let rebuild_bytes = limit * REBUILD_BYTES_PER_NODE;
+        assert!(capacity * std::mem::size_of::<Node>() + map_bytes + rebuild_bytes <= max_memory);
+    }
+
+    /// Searches the position with the default config, which enables the solver.
+    fn solve(notation: &str) -> DecisionReport {
+        assert!(MctsConfig::default().solver);
+        let board = Board::from_notation(notation).unwrap();
+        let mut mcts = MctsEngine::with_seed(board, 5);
+        mcts.run_iterations(1000);
+        mcts.decision_report().unwrap()
+    }
+
+    #[test]
+    fn solver_proves_win_in_one() {
+        // X has won the top left and top middle sub-boards and completes the top right one.
+        let report = solve("XXXOO4/XXXOO4/XX1OO4/1OO6/9/9/9/9/9 X 2");
+        assert_eq!(report.source, DecisionSource::Proven);
+        assert_eq!(report.chosen.m, Move { major: 2, minor: 2 });
+        assert_eq!(report.chosen.proven, Some(Outcome::X));
+    }
+
+    #[test]
+    fn solver_forces_block() {
+        // O threatens to win the top right sub-board and the game. Playing in the middle right
+        // cell sends O to a decided sub-board, from where they can play the winning move.
+        let report = solve("OOOXX4/OOOXX4/OO1XO1OXX/9/9/XXX6/9/9/9 X 2");
+        assert_eq!(report.source, DecisionSource::Forced);
+        assert_eq!(report.chosen.m, Move { major: 2, minor: 2 });
+        assert_eq!(report.chosen.proven, None);
+        assert_eq!(report.runners_up[0].proven, Some(Outcome::O));
+    }
+}
@@ -0,0 +1,53 @@
+//! Error types.
+
+use thiserror::Error;
+use uttt_core::{ParseError, PositionError};
+
+/// Error returned by the engine when it cannot search or pick a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum EngineError {
+    #[error("position does not have any legal moves")]
+    NoLegalMoves,
+}
+
+/// Error returned when parsing a [`ReproBundle`](crate::ReproBundle).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ReproError {
+    #[error("invalid repro bundle: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Position(#[from] PositionError),
+}
+
+/// Reason why [`MctsEngine::repro`](crate::MctsEngine::repro) has no
+/// [`ReproBundle`](crate::ReproBundle) for the last search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ReproUnavailable {
+    #[error("recording repro bundles is disabled")]
+    Disabled,
+    #[error("no search was performed since the position was set")]
+    NoSearch,
+    #[error("custom selection and rollout policies cannot be recorded")]
+    CustomPolicy,
+    #[error("searches using a transposition table are not reproducible")]
+    TranspositionTable,
+    #[error("opponent models cannot be recorded")]
+    OpponentModel,
+    #[error("the engine was not seeded with a StdRng")]
+    Unseeded,
+    #[error("the search tree was reused from a previous position")]
+    ReusedTree,
+    #[error("GPU searches are not reproducible")]
+    Gpu,
+}
+
+/// Error returned when parsing an [`OpeningBook`](crate::OpeningBook).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BookError {
+    #[error("invalid opening book: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
@@ -0,0 +1,127 @@
+//! Short textual explanations of moves, e.g. for showing why the AI played a move.
+//!
+//! The explanations only look at the position right before and after the move (which sub-boards
+//! are won, blocked or threatened), not at the search tree.
+
+use std::fmt::{self, Display, Formatter};
+
+use uttt_core::{BitBoard, Board, Move, NextSubBoard, Player};
+
+/// Names of the sub-boards (and cells), indexed by their position.
+const POSITION_NAMES: [&str; 9] = [
+    "top-left",
+    "top",
+    "top-right",
+    "left",
+    "center",
+    "right",
+    "bottom-left",
+    "bottom",
+    "bottom-right",
+];
+
+/// A reason why a move is interesting. Displays as a short sentence fragment, e.g. "creates a
+/// double threat in the top-left board".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveReason {
+    /// The move wins the game.
+    WinsGame,
+    /// The move wins the sub-board.
+    WinsSubBoard { major: u32 },
+    /// The move blocks the only cell where the opponent could complete a line in the sub-board.
+    BlocksSubBoard { major: u32 },
+    /// After the move, there are two or more cells that would complete a line for the player in
+    /// the sub-board.
+    CreatesDoubleThreat { major: u32 },
+    /// The move sends the opponent to a decided sub-board, so they can play anywhere.
+    SendsToDecidedBoard,
+    /// The move sends the opponent to a sub-board which they can win with their next move.
+    SendsToOpponentWin { major: u32 },
+    /// The move is the only legal move.
+    OnlyMove,
+}
+
+impl Display for MoveReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::WinsGame => write!(f, "wins the game"),
+            Self::WinsSubBoard { major } => {
+                write!(f, "wins the {} board", POSITION_NAMES[major as usize])
+            }
+            Self::BlocksSubBoard { major } => write!(
+                f,
+                "only move that blocks the opponent in the {} board",
+                POSITION_NAMES[major as usize]
+            ),
+            Self::CreatesDoubleThreat { major } => write!(
+                f,
+                "creates a double threat in the {} board",
+                POSITION_NAMES[major as usize]
+            ),
+            Self::SendsToDecidedBoard => write!(
+                f,
+                "sends the opponent to an already decided board, so they can play anywhere"
+            ),
+            Self::SendsToOpponentWin { major } => write!(
+                f,
+                "sends the opponent to the {} board where they can win it",
+                POSITION_NAMES[major as usize]
+            ),
+            Self::OnlyMove => write!(f, "only legal move"),
+        }
+    }
+}
+
+/// Returns the cells of the sub-board that would complete a line for `player`.
+fn completing_cells(board: &Board, major: u32, player: Player) -> BitBoard {
+    let sub_board = board.board[major as usize];
+    sub_board[player].completing_moves(sub_board.x | sub_board.o)
+}
+
+/// Returns `true` if the sub-board was won or tied.
+fn is_decided(board: &Board, major: u32) -> bool {
+    let sub_wins = board.sub_wins;
+    (sub_wins.x.0 | sub_wins.o.0 | sub_wins.tie.0) & (1 << major) != 0
+}
+
+/// Explains a legal move in the position, most important reason first. Returns an empty list if
+/// the move is unremarkable or illegal.
+pub fn explain_move(board: &Board, m: Move) -> Vec<MoveReason> {
+    let Some(next) = board.advance_state(m) else {
+        return Vec::new();
+    };
+    let player = board.player_to_move;
+    let opponent = next.player_to_move;
+    let mut reasons = Vec::new();
+
+    if board.winner().is_none() && next.winner().is_some() {
+        reasons.push(MoveReason::WinsGame);
+    }
+    if next.sub_wins[player].0 & (1 << m.major) != 0 {
+        reasons.push(MoveReason::WinsSubBoard { major: m.major });
+    } else {
+        if completing_cells(board, m.major, opponent).0 == 1 << m.minor {
+            reasons.push(MoveReason::BlocksSubBoard { major: m.major });
+        }
+        if !is_decided(&next, m.major)
+            && completing_cells(&next, m.major, player).0.count_ones() >= 2
+        {
+            reasons.push(MoveReason::CreatesDoubleThreat { major: m.major });
+        }
+    }
+    if next.winner().is_none() {
+        match next.constraint() {
+            NextSubBoard::Any => reasons.push(MoveReason::SendsToDecidedBoard),
+            NextSubBoard::Sub(major) if completing_cells(&next, major as u32, opponent).0 != 0 => {
+                reasons.push(MoveReason::SendsToOpponentWin {
+                    major: major as u32,
+                })
+            }
+            NextSubBoard::Sub(_) => {}
+        }
+    }
+    if board.count_moves() == 1 {
+        reasons.push(MoveReason::OnlyMove);
+    }
+    reasons
+}
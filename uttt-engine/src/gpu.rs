@@ -0,0 +1,235 @@
+//! Experimental GPU rollouts using a `wgpu` compute shader.
+//!
+//! Boards are uploaded as a [`BoardBatch`] (struct of arrays) and every board is played out to the
+//! end by a single shader invocation. This is only available with the `gpu` feature.
+
+use std::sync::mpsc;
+
+use thiserror::Error;
+use wgpu::util::DeviceExt;
+
+use uttt_core::{Board, Outcome, Player};
+
+/// Number of `u32` fields stored per board in a [`BoardBatch`].
+const FIELDS: usize = 8;
+/// Must match `@workgroup_size` in `rollout.wgsl`.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// A batch of boards stored as a struct of arrays, ready to be uploaded to the GPU.
+///
+/// Each board is stored as 8 `u32` fields. Field `f` of board `i` lives at index
+/// `f * len + i` of [`as_words`](Self::as_words):
+///
+/// - fields `0..3`: X stones, three sub-boards of 9 bits each per word.
+/// - fields `3..6`: O stones, same layout as X.
+/// - field `6`: won sub-boards, bits `0..9` for X, `9..18` for O and `18..27` for ties.
+/// - field `7`: bit `0` is the player to move (`0` for X, `1` for O), the remaining bits are the
+///   next sub-board.
+#[derive(Default, Clone)]
+pub struct BoardBatch {
+    fields: [Vec<u32>; FIELDS],
+}
+
+impl BoardBatch {
+    /// Create a new empty [`BoardBatch`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a board to the batch.
+    pub fn push(&mut self, board: &Board) {
+        for word in 0..3 {
+            let mut x = 0;
+            let mut o = 0;
+            for i in 0..3 {
+                let sub_board = board.board[word * 3 + i];
+                x |= (sub_board.x.0 as u32) << (i * 9);
+                o |= (sub_board.o.0 as u32) << (i * 9);
+            }
+            self.fields[word].push(x);
+            self.fields[3 + word].push(o);
+        }
+        let wins = board.sub_wins.x.0 as u32
+            | (board.sub_wins.o.0 as u32) << 9
+            | (board.sub_wins.tie.0 as u32) << 18;
+        self.fields[6].push(wins);
+        let player = match board.player_to_move {
+            Player::X => 0,
+            Player::O => 1,
+        };
+        self.fields[7].push(player | (u8::from(board.constraint()) as u32) << 1);
+    }
+
+    /// Returns the number of boards in the batch.
+    pub fn len(&self) -> usize {
+        self.fields[0].len()
+    }
+
+    /// Returns `true` if the batch does not contain any boards.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all boards from the batch.
+    pub fn clear(&mut self) {
+        for field in &mut self.fields {
+            field.clear();
+        }
+    }
+
+    /// Returns the batch as a flat, field-major list of words.
+    pub fn as_words(&self) -> Vec<u32> {
+        self.fields.concat()
+    }
+}
+
+/// Error returned when the GPU rollout backend cannot be used.
+#[derive(Debug, Error)]
+pub enum GpuError {
+    /// No suitable GPU adapter was found.
+    #[error("no suitable GPU adapter found")]
+    NoAdapter,
+    /// The adapter refused to create a device.
+    #[error("failed to request GPU device: {0}")]
+    RequestDevice(#[from] wgpu::RequestDeviceError),
+    /// The results could not be read back from the GPU.
+    #[error("failed to read rollout results from the GPU")]
+    ReadBack,
+}
+
+/// Runs batches of random rollouts on the GPU.
+pub struct GpuRollouts {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuRollouts {
+    /// Create a new [`GpuRollouts`] using the default GPU adapter. Blocks until the device is
+    /// ready.
+    pub fn new() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .map_err(|_| GpuError::NoAdapter)?;
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default()))
+                .map_err(GpuError::RequestDevice)?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rollout"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("gpu/rollout.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("rollout"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+        })
+    }
+
+    /// Plays out every board in the batch with uniformly random moves. Returns the winner and
+    /// the number of moves simulated for each board, in the same order as the batch.
+    pub fn rollout(&self, batch: &BoardBatch, seed: u32) -> Result<Vec<(Outcome, u32)>, GpuError> {
+        if batch.is_empty() {
+            return Ok(Vec::new());
+        }
+        let count = batch.len() as u32;
+
+        let params = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("rollout params"),
+                contents: bytemuck::cast_slice(&[count, seed]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let boards = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("rollout boards"),
+                contents: bytemuck::cast_slice(&batch.as_words()),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let results_size = (count as u64) * 2 * std::mem::size_of::<u32>() as u64;
+        let results = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rollout results"),
+            size: results_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rollout staging"),
+            size: results_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rollout"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: boards.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: results.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&results, 0, &staging, 0, results_size);
+        self.queue.submit([encoder.finish()]);
+
+        // Read back the results.
+        let (tx, rx) = mpsc::channel();
+        staging.map_async(wgpu::MapMode::Read, .., move |result| {
+            let _ = tx.send(result);
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|_| GpuError::ReadBack)?;
+        rx.recv()
+            .map_err(|_| GpuError::ReadBack)?
+            .map_err(|_| GpuError::ReadBack)?;
+
+        let view = staging.get_mapped_range(..);
+        let words: &[u32] = bytemuck::cast_slice(&view);
+        let results = words
+            .chunks_exact(2)
+            .map(|result| {
+                let outcome = match result[0] {
+                    0 => Outcome::X,
+                    1 => Outcome::O,
+                    _ => Outcome::Tie,
+                };
+                (outcome, result[1])
+            })
+            .collect();
+        Ok(results)
+    }
+}
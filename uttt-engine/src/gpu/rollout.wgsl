@@ -0,0 +1,133 @@
+// Random rollouts for a batch of Ultimate TicTacToe boards.
+//
+// Each invocation plays uniformly random moves from one board until the game is decided. Boards
+// are stored field-major (struct of arrays), see `BoardBatch` for the layout:
+//
+// - fields 0..3: X stones, three sub-boards of 9 bits each per word.
+// - fields 3..6: O stones, same layout as X.
+// - field 6: won sub-boards, bits 0..9 for X, 9..18 for O and 18..27 for ties.
+// - field 7: bit 0 is the player to move (0 = X, 1 = O), bits 1.. are the next sub-board.
+//
+// For each board, two words are written into `results`: the outcome (0 = X, 1 = O, 2 = tie) and
+// the number of moves simulated.
+
+struct Params {
+    count: u32,
+    seed: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> boards: array<u32>;
+@group(0) @binding(2) var<storage, read_write> results: array<u32>;
+
+const FULL: u32 = 0x1ffu;
+
+fn has_line(b: u32) -> bool {
+    let rows = b & (b >> 1u) & (b >> 2u) & 0x49u;
+    let cols = b & (b >> 3u) & (b >> 6u) & 0x7u;
+    return rows != 0u || cols != 0u || (b & 0x111u) == 0x111u || (b & 0x54u) == 0x54u;
+}
+
+fn cells(words: ptr<function, array<u32, 3>>, s: u32) -> u32 {
+    return ((*words)[s / 3u] >> ((s % 3u) * 9u)) & FULL;
+}
+
+// PCG hash based random number generator.
+fn next_random(state: ptr<function, u32>) -> u32 {
+    let old = *state;
+    *state = old * 747796405u + 2891336453u;
+    let word = ((old >> ((old >> 28u) + 4u)) ^ old) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    let n = params.count;
+    if (i >= n) {
+        return;
+    }
+
+    var x = array<u32, 3>(boards[i], boards[n + i], boards[2u * n + i]);
+    var o = array<u32, 3>(boards[3u * n + i], boards[4u * n + i], boards[5u * n + i]);
+    var wins = boards[6u * n + i];
+    let state = boards[7u * n + i];
+    var player = state & 1u;
+    var next = state >> 1u;
+
+    var rng = params.seed ^ (i * 2654435761u);
+    var plies = 0u;
+    var outcome = 2u;
+    loop {
+        let x_wins = wins & FULL;
+        let o_wins = (wins >> 9u) & FULL;
+        let decided = x_wins | o_wins | (wins >> 18u);
+        if (has_line(x_wins)) {
+            outcome = 0u;
+            break;
+        }
+        if (has_line(o_wins)) {
+            outcome = 1u;
+            break;
+        }
+        if (decided == FULL) {
+            outcome = 2u;
+            break;
+        }
+
+        // Count the legal moves.
+        var total = 0u;
+        for (var s = 0u; s < 9u; s++) {
+            if (next == s || (next == 9u && ((decided >> s) & 1u) == 0u)) {
+                total += 9u - countOneBits(cells(&x, s) | cells(&o, s));
+            }
+        }
+
+        // Pick a random legal move.
+        var k = next_random(&rng) % total;
+        var major = 0u;
+        var minor = 0u;
+        for (var s = 0u; s < 9u; s++) {
+            if (next == s || (next == 9u && ((decided >> s) & 1u) == 0u)) {
+                var empty = ~(cells(&x, s) | cells(&o, s)) & FULL;
+                let empty_count = countOneBits(empty);
+                if (k < empty_count) {
+                    for (var j = 0u; j < k; j++) {
+                        empty &= empty - 1u;
+                    }
+                    major = s;
+                    minor = firstTrailingBit(empty);
+                    break;
+                }
+                k -= empty_count;
+            }
+        }
+
+        // Apply the move.
+        let bit = 1u << (minor + (major % 3u) * 9u);
+        var stones = 0u;
+        if (player == 0u) {
+            x[major / 3u] |= bit;
+            stones = cells(&x, major);
+        } else {
+            o[major / 3u] |= bit;
+            stones = cells(&o, major);
+        }
+        if (has_line(stones)) {
+            wins |= 1u << (major + 9u * player);
+        } else if ((cells(&x, major) | cells(&o, major)) == FULL) {
+            wins |= 1u << (major + 18u);
+        }
+        let now_decided = (wins | (wins >> 9u) | (wins >> 18u)) & FULL;
+        if (((now_decided >> minor) & 1u) != 0u) {
+            next = 9u;
+        } else {
+            next = minor;
+        }
+        player ^= 1u;
+        plies += 1u;
+    }
+
+    results[2u * i] = outcome;
+    results[2u * i + 1u] = plies;
+}
@@ -0,0 +1,39 @@
+//! Ultimate TicTacToe AI engine written in Rust.
+
+mod alphabeta;
+mod book;
+mod cache;
+mod clock;
+mod config;
+mod engine;
+mod error;
+mod explain;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod opponent;
+mod ordering;
+mod pns;
+mod report;
+mod repro;
+mod rollout;
+mod search;
+mod selection;
+
+pub use alphabeta::*;
+pub use book::*;
+pub use cache::*;
+pub use clock::*;
+pub use config::*;
+pub use engine::*;
+pub use error::*;
+pub use explain::*;
+#[cfg(feature = "gpu")]
+pub use gpu::*;
+pub use opponent::*;
+pub use ordering::*;
+pub use pns::*;
+pub use report::*;
+pub use repro::*;
+pub use rollout::*;
+pub use search::*;
+pub use selection::*;
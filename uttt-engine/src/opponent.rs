@@ -0,0 +1,75 @@
+//! Model of the tendencies of an opponent, learned from their moves during a game.
+
+use uttt_core::{Board, Move, Player};
+
+/// Tracks which cells of a sub-board an opponent prefers and how often they miss moves that win a
+/// sub-board. See [`MctsEngine::set_opponent_model`](crate::MctsEngine::set_opponent_model).
+///
+/// The estimates start from a few imaginary observations of a neutral player, who plays every cell
+/// equally often and takes every other chance to win a sub-board, so the model does not jump to
+/// conclusions after the first few moves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpponentModel {
+    player: Player,
+    /// Number of moves played in each cell of a sub-board.
+    cells: [f32; 9],
+    /// Number of positions where the opponent could win a sub-board.
+    win_chances: f32,
+    /// Number of those positions where they did win a sub-board.
+    wins_taken: f32,
+}
+
+impl OpponentModel {
+    /// Create a new [`OpponentModel`] for the given player without any observations.
+    pub fn new(player: Player) -> Self {
+        Self {
+            player,
+            cells: [1.0; 9],
+            win_chances: 2.0,
+            wins_taken: 1.0,
+        }
+    }
+
+    /// Returns the modeled player.
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    /// Observes the move `m` played in `board`. Moves of the other player are ignored, so every
+    /// move of a game can be passed to this.
+    pub fn observe(&mut self, board: &Board, m: Move) {
+        if board.player_to_move != self.player || !board.is_legal(m) {
+            return;
+        }
+        self.cells[m.minor as usize] += 1.0;
+        let could_win = board.moves().any(|m| wins_sub_board(board, m));
+        if could_win {
+            self.win_chances += 1.0;
+            if wins_sub_board(board, m) {
+                self.wins_taken += 1.0;
+            }
+        }
+    }
+
+    /// Returns how much more likely the opponent is to play `m` in `board` than the neutral player,
+    /// e.g. `2.0` if they are twice as likely. Only meaningful if the opponent is to move.
+    pub fn move_weight(&self, board: &Board, m: Move) -> f32 {
+        let total: f32 = self.cells.iter().sum();
+        let cell = self.cells[m.minor as usize] * 9.0 / total;
+        let take_rate = self.wins_taken / self.win_chances;
+        let win = if wins_sub_board(board, m) {
+            // The neutral player takes half of their chances.
+            2.0 * take_rate
+        } else {
+            1.0
+        };
+        cell * win
+    }
+}
+
+/// Returns `true` if playing `m` wins a sub-board for the player to move.
+fn wins_sub_board(board: &Board, m: Move) -> bool {
+    board.board[m.major as usize][board.player_to_move]
+        .advance_bitfield_state(m.minor)
+        .has_line()
+}
@@ -0,0 +1,361 @@
+//! Proof-number search, for definitive verdicts on positions late in the game.
+
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use uttt_core::{Board, Move, MoveList, Outcome};
+
+use crate::{Clock, Engine, InstantClock, SearchHandle, SearchLimits, SearchResult};
+
+/// Proof or disproof number of a node that cannot be proven, respectively disproven.
+const INFINITY: u32 = u32::MAX;
+/// Number of expanded nodes between two clock reads.
+const EXPANSIONS_PER_TIME_CHECK: u64 = 256;
+/// [`PnsNode::first_child`] of a node that was not expanded yet.
+const UNEXPANDED: u32 = u32::MAX;
+
+struct PnsNode {
+    board: Board,
+    parent: u32,
+    /// The children of a node are stored next to each other, starting at `first_child`.
+    first_child: u32,
+    child_count: u8,
+    /// Minimum number of leaves that must be proven to prove the node.
+    proof: u32,
+    /// Minimum number of leaves that must be disproven to disprove the node.
+    disproof: u32,
+}
+
+/// Verdict of a [`PnsEngine`] on a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PnsResult {
+    /// Outcome of the game with best play, or `None` if the node budget was not enough to prove
+    /// it.
+    pub outcome: Option<Outcome>,
+    /// A move achieving `outcome`, or the most promising move if the outcome is unknown. For a lost
+    /// position, this is the move that delays the loss the longest. `None` if the game is over.
+    pub best_move: Option<Move>,
+    /// Depth of the tree that proves `outcome`: for a win or a loss, the number of moves the game
+    /// lasts at most with best play from the winner. For a draw, the depth of the proof that the
+    /// player to move does not lose. `None` if the outcome is unknown.
+    pub depth: Option<u32>,
+    /// Number of nodes created by the search.
+    pub nodes: u64,
+}
+
+/// Solver that proves whether a position is won, drawn or lost with proof-number search.
+///
+/// Proof-number search grows the tree towards the leaves that are cheapest to prove or disprove a
+/// goal for the player to move, which makes it much faster than alpha-beta at finding forced wins.
+/// The engine first tries to prove a win, and if that is disproven, tries to prove at least a
+/// draw. It does not guess: unless the whole proof fits in the node budget, the outcome stays
+/// unknown. Late middlegame positions are usually tractable within a million nodes or two.
+///
+/// Every node stores its position, so the tree takes about 80 bytes per node.
+pub struct PnsEngine<C: Clock = InstantClock> {
+    board: Board,
+    clock: C,
+    nodes: Vec<PnsNode>,
+    /// Whether a tie reaches the goal of the current search, besides a win of the root player.
+    accept_tie: bool,
+    /// Set by [`SearchHandle::stop`].
+    stop: Arc<AtomicBool>,
+}
+
+impl PnsEngine {
+    /// Create a new [`PnsEngine`] for the given position.
+    pub fn for_position(board: Board) -> Self {
+        Self::with_clock(board, InstantClock::new())
+    }
+}
+
+impl<C: Clock> PnsEngine<C> {
+    /// Create a new [`PnsEngine`] for the given position that measures time budgets with the given
+    /// [`Clock`].
+    pub fn with_clock(board: Board, clock: C) -> Self {
+        Self {
+            board,
+            clock,
+            nodes: Vec::new(),
+            accept_tie: false,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns the position being solved.
+    pub fn position(&self) -> Board {
+        self.board
+    }
+
+    /// Returns a handle that stops the searches of this engine from another thread. The search
+    /// returns as if it had run out of nodes.
+    pub fn search_handle(&self) -> SearchHandle {
+        SearchHandle {
+            stop: self.stop.clone(),
+        }
+    }
+
+    /// Tries to prove the outcome of the position within `limits`. The node limit bounds the
+    /// number of nodes of the trees, the depth limit is ignored.
+    pub fn solve(&mut self, limits: SearchLimits) -> PnsResult {
+        let start = self.clock.now();
+        let deadline = limits
+            .time_ms
+            .map(|ms| start + Duration::from_millis(ms as u64));
+        let player = self.board.player_to_move;
+        if let Some(outcome) = self.board.winner() {
+            return PnsResult {
+                outcome: Some(outcome),
+                best_move: None,
+                depth: Some(0),
+                nodes: 0,
+            };
+        }
+
+        let mut nodes = 0;
+        let mut budget = limits.nodes;
+        let win = self.prove(false, budget, deadline);
+        nodes += self.nodes.len() as u64;
+        let outcome = match win {
+            Some(true) => Some(Outcome::from(player)),
+            Some(false) => {
+                budget = budget.map(|budget| budget.saturating_sub(nodes));
+                let draw = self.prove(true, budget, deadline);
+                nodes += self.nodes.len() as u64;
+                match draw {
+                    Some(true) => Some(Outcome::Tie),
+                    Some(false) => Some(Outcome::from(player.opponent())),
+                    None => None,
+                }
+            }
+            None => None,
+        };
+
+        let root = &self.nodes[0];
+        let best_move = if root.proof == 0 {
+            // The move proving the goal with the shortest proof.
+            self.best_child(|engine, child| {
+                (engine.nodes[child as usize].proof == 0).then(|| engine.proof_depth(child, true))
+            })
+        } else if root.disproof == 0 {
+            // Every move loses, pick the one resisting the longest.
+            self.best_child(|engine, child| Some(Reverse(engine.proof_depth(child, false))))
+        } else {
+            self.best_child(|engine, child| Some(engine.nodes[child as usize].proof))
+        };
+        PnsResult {
+            outcome,
+            best_move,
+            depth: outcome.map(|_| self.proof_depth(0, root.proof == 0)),
+            nodes,
+        }
+    }
+
+    /// Returns the move of the root child with the lowest `key`, skipping children without a key.
+    fn best_child<K: Ord>(&self, key: impl Fn(&Self, u32) -> Option<K>) -> Option<Move> {
+        let root = &self.nodes[0];
+        let children = root.first_child..root.first_child + root.child_count as u32;
+        let child = children
+            .filter_map(|child| Some((child, key(self, child)?)))
+            .min_by(|(_, a), (_, b)| a.cmp(b))?
+            .0;
+        self.nodes[child as usize].board.last_move()
+    }
+
+    /// Runs proof-number search on a new tree for the goal of the root player winning, or also
+    /// tying if `accept_tie` is set. Returns whether the goal was proven, or `None` if the tree
+    /// reached `max_nodes` nodes, the deadline passed or the search was stopped first.
+    fn prove(
+        &mut self,
+        accept_tie: bool,
+        max_nodes: Option<u64>,
+        deadline: Option<Duration>,
+    ) -> Option<bool> {
+        self.accept_tie = accept_tie;
+        self.nodes.clear();
+        let (proof, disproof) = self.initial_numbers(&self.board);
+        self.nodes.push(PnsNode {
+            board: self.board,
+            parent: u32::MAX,
+            first_child: UNEXPANDED,
+            child_count: 0,
+            proof,
+            disproof,
+        });
+
+        let mut expansions = 0u64;
+        loop {
+            let root = &self.nodes[0];
+            if root.proof == 0 {
+                return Some(true);
+            }
+            if root.disproof == 0 {
+                return Some(false);
+            }
+            if max_nodes.is_some_and(|max| self.nodes.len() as u64 >= max)
+                || (self.stop.load(Ordering::Relaxed) && self.stop.swap(false, Ordering::Relaxed))
+            {
+                return None;
+            }
+            expansions += 1;
+            if expansions.is_multiple_of(EXPANSIONS_PER_TIME_CHECK)
+                && deadline.is_some_and(|deadline| self.clock.now() >= deadline)
+            {
+                return None;
+            }
+
+            let leaf = self.most_proving_node();
+            self.expand(leaf);
+            self.update_ancestors(leaf);
+        }
+    }
+
+    /// Returns `true` if the root player tries to prove the goal at this node, i.e. it is their
+    /// turn, so a single proven child proves the node.
+    fn is_or_node(&self, board: &Board) -> bool {
+        board.player_to_move == self.board.player_to_move
+    }
+
+    /// Returns the proof and disproof numbers of a new leaf. Terminal positions are proven or
+    /// disproven, other leaves favor positions where the side to move has few moves.
+    fn initial_numbers(&self, board: &Board) -> (u32, u32) {
+        match board.winner() {
+            Some(outcome) if self.reaches_goal(outcome) => (0, INFINITY),
+            Some(_) => (INFINITY, 0),
+            None if self.is_or_node(board) => (1, board.count_moves()),
+            None => (board.count_moves(), 1),
+        }
+    }
+
+    fn reaches_goal(&self, outcome: Outcome) -> bool {
+        outcome == Outcome::from(self.board.player_to_move)
+            || (self.accept_tie && outcome == Outcome::Tie)
+    }
+
+    /// Walks down from the root to the leaf whose proof would contribute the most to proving or
+    /// disproving the root.
+    fn most_proving_node(&self) -> u32 {
+        let mut id = 0;
+        loop {
+            let node = &self.nodes[id as usize];
+            if node.first_child == UNEXPANDED {
+                return id;
+            }
+            let children = node.first_child..node.first_child + node.child_count as u32;
+            id = if self.is_or_node(&node.board) {
+                children.min_by_key(|&child| self.nodes[child as usize].proof)
+            } else {
+                children.min_by_key(|&child| self.nodes[child as usize].disproof)
+            }
+            .expect("expanded node has children");
+        }
+    }
+
+    fn expand(&mut self, id: u32) {
+        let board = self.nodes[id as usize].board;
+        let mut moves = MoveList::new();
+        board.generate_moves_into(&mut moves);
+        let first_child = self.nodes.len() as u32;
+        for &m in moves.iter() {
+            // SAFETY: m is a legal move.
+            let next = unsafe { board.advance_state_unsafe(m) };
+            let (proof, disproof) = self.initial_numbers(&next);
+            self.nodes.push(PnsNode {
+                board: next,
+                parent: id,
+                first_child: UNEXPANDED,
+                child_count: 0,
+                proof,
+                disproof,
+            });
+        }
+        let node = &mut self.nodes[id as usize];
+        node.first_child = first_child;
+        node.child_count = moves.len() as u8;
+    }
+
+    /// Recomputes the proof and disproof numbers of the node and its ancestors from their children,
+    /// up to the first one that does not change.
+    fn update_ancestors(&mut self, mut id: u32) {
+        while id != u32::MAX {
+            let node = &self.nodes[id as usize];
+            let children = node.first_child..node.first_child + node.child_count as u32;
+            let or_node = self.is_or_node(&node.board);
+            // An OR node takes the smallest proof number and the sum of the disproof numbers of
+            // its children, an AND node the other way around.
+            let (mut min, mut sum) = (INFINITY, 0u32);
+            for child in children {
+                let child = &self.nodes[child as usize];
+                let (a, b) = if or_node {
+                    (child.proof, child.disproof)
+                } else {
+                    (child.disproof, child.proof)
+                };
+                min = min.min(a);
+                sum = sum.saturating_add(b);
+            }
+            let (proof, disproof) = if or_node { (min, sum) } else { (sum, min) };
+            let node = &mut self.nodes[id as usize];
+            if node.proof == proof && node.disproof == disproof {
+                return;
+            }
+            node.proof = proof;
+            node.disproof = disproof;
+            id = node.parent;
+        }
+    }
+
+    /// Returns the depth of the subtree proving (if `proof` is set) or disproving the node, which
+    /// must be proven or disproven accordingly.
+    fn proof_depth(&self, id: u32, proof: bool) -> u32 {
+        let node = &self.nodes[id as usize];
+        if node.first_child == UNEXPANDED {
+            return 0;
+        }
+        let children = node.first_child..node.first_child + node.child_count as u32;
+        let decided = |child: &u32| {
+            let child = &self.nodes[*child as usize];
+            if proof {
+                child.proof == 0
+            } else {
+                child.disproof == 0
+            }
+        };
+        // At a node where a single decided child suffices, the shortest one is used. Otherwise
+        // every child is needed.
+        let depths = children
+            .filter(decided)
+            .map(|child| self.proof_depth(child, proof));
+        let single = self.is_or_node(&node.board) == proof;
+        1 + if single { depths.min() } else { depths.max() }.unwrap_or(0)
+    }
+}
+
+/// The node limit bounds the size of the trees, see [`PnsEngine::solve`]. The win rate is `1.0`,
+/// `0.5` or `0.0` if the outcome is proven, and unknown otherwise.
+impl<C: Clock> Engine for PnsEngine<C> {
+    fn set_position(&mut self, board: Board) {
+        self.board = board;
+        self.nodes.clear();
+    }
+
+    fn go(&mut self, limits: SearchLimits) -> SearchResult {
+        let start = self.clock.now();
+        let player = self.board.player_to_move;
+        let result = self.solve(limits);
+        SearchResult {
+            best_move: result.best_move,
+            win_rate: result.outcome.map(|outcome| outcome.score_for(player)),
+            pv: result.best_move.into_iter().collect(),
+            nodes: result.nodes,
+            depth: result.depth,
+            elapsed: self.clock.now() - start,
+        }
+    }
+
+    fn search_handle(&self) -> SearchHandle {
+        self.search_handle()
+    }
+}
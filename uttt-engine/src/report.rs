@@ -0,0 +1,128 @@
+//! Summaries of why a move was chosen, for frontends and tuning.
+
+use std::fmt::{self, Display, Formatter};
+
+use uttt_core::{Move, Outcome};
+
+/// Number of runners-up included in a [`DecisionReport`].
+pub const DECISION_REPORT_RUNNERS_UP: usize = 3;
+/// A decision counts as a close call if the chosen move has less than this many times the visits
+/// of the best runner-up.
+pub const CLOSE_CALL_VISIT_RATIO: f32 = 1.5;
+
+/// What a move was chosen by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecisionSource {
+    /// The move was taken from an [`OpeningBook`](crate::OpeningBook).
+    Book,
+    /// The move is the only legal move.
+    OnlyMove,
+    /// The move is proven to win or draw.
+    Proven,
+    /// Every other move is proven to lose.
+    Forced,
+    /// The move has the most visits of the search.
+    Search,
+}
+
+/// Statistics of a move in a [`DecisionReport`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveSummary {
+    pub m: Move,
+    /// Number of visits of the move, or the rounded discounted number of games for book moves.
+    pub visits: u32,
+    /// Win rate of the move for the player to move.
+    pub win_rate: f32,
+    /// Outcome of the game with best play after the move, if the searched tree proves it.
+    pub proven: Option<Outcome>,
+}
+
+/// A move with the statistics the search gathered for it, see
+/// [`MctsEngine::best_move_with_stats`](crate::MctsEngine::best_move_with_stats).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveEval {
+    pub mv: Move,
+    /// Number of visits of the move.
+    pub visits: u32,
+    /// Win rate of the move for the player to move, counting ties as half a win.
+    pub win_rate: f32,
+    /// Principal variation: the line of play the engine expects, starting with `mv` and following
+    /// the best move of each position after it as far as the tree was searched.
+    pub pv: Vec<Move>,
+}
+
+/// Summary of why a move was chosen over the alternatives, see
+/// [`MctsEngine::decision_report`](crate::MctsEngine::decision_report) and
+/// [`OpeningBook::decision_report`](crate::OpeningBook::decision_report).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionReport {
+    pub chosen: MoveSummary,
+    /// The next best moves, best first. At most [`DECISION_REPORT_RUNNERS_UP`] are included.
+    pub runners_up: Vec<MoveSummary>,
+    pub source: DecisionSource,
+}
+
+impl DecisionReport {
+    /// Returns how many times more visits the chosen move has than the best runner-up, or `None`
+    /// if there is no runner-up.
+    pub fn visit_ratio(&self) -> Option<f32> {
+        let runner_up = self.runners_up.first()?;
+        Some(self.chosen.visits as f32 / runner_up.visits.max(1) as f32)
+    }
+
+    /// Returns how much higher the win rate of the chosen move is than that of the best runner-up,
+    /// or `None` if there is no runner-up. Can be negative, since moves are ranked by visits.
+    pub fn win_rate_margin(&self) -> Option<f32> {
+        let runner_up = self.runners_up.first()?;
+        Some(self.chosen.win_rate - runner_up.win_rate)
+    }
+
+    /// Returns `true` if the search barely preferred the chosen move over the best runner-up. See
+    /// [`CLOSE_CALL_VISIT_RATIO`].
+    pub fn is_close_call(&self) -> bool {
+        self.source == DecisionSource::Search
+            && self
+                .visit_ratio()
+                .is_some_and(|ratio| ratio < CLOSE_CALL_VISIT_RATIO)
+    }
+}
+
+impl Display for DecisionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.chosen.m)?;
+        match self.source {
+            DecisionSource::Book => write!(f, " from book"),
+            DecisionSource::OnlyMove => write!(f, " (only move)"),
+            DecisionSource::Proven => match self.chosen.proven {
+                Some(Outcome::Tie) => write!(f, " (forced draw)"),
+                _ => write!(f, " (forced win)"),
+            },
+            DecisionSource::Forced => write!(f, " (forced: only non-losing move)"),
+            DecisionSource::Search => {
+                write!(
+                    f,
+                    ": {} visits, {:.0}%",
+                    self.chosen.visits,
+                    self.chosen.win_rate * 100.0
+                )?;
+                if let Some(runner_up) = self.runners_up.first() {
+                    let closeness = if self.is_close_call() {
+                        "close call"
+                    } else {
+                        "clear choice"
+                    };
+                    write!(
+                        f,
+                        "; next best {},{}: {} visits, {:.0}% ({})",
+                        runner_up.m.major,
+                        runner_up.m.minor,
+                        runner_up.visits,
+                        runner_up.win_rate * 100.0,
+                        closeness
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,497 @@
+//! Reproduction bundles for searches.
+//!
+//! A search is fully determined by the seed of the engine, its [`MctsConfig`], the searched
+//! position and the number of iterations performed since the tree was created. A [`ReproBundle`]
+//! stores these in a small text format, so that a search that happened on another machine (e.g. in a bug report) can be
+//! replayed exactly.
+//!
+//! The first line of a bundle is its format version. Bundles written by the previous version of
+//! the format can still be read.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use uttt_core::{BitBoard, Board, Move, NextSubBoard, Player, Rules, SubBoard};
+
+use crate::{
+    HeuristicRollout, MctsConfig, MctsEngine, NodeLimitPolicy, ReplacementPolicy, ReproError,
+    Rollout, SelectionFormula,
+};
+
+/// Version of the text format written by [`ReproBundle`].
+///
+/// - `1`: initial version.
+/// - `2`: adds the `rules` line. Version `1` bundles are read with the default rules.
+/// - `3`: adds the [`MctsConfig`] lines. Older bundles were written before
+///   [`MctsConfig::solver`] existed and are read with the default config without the solver.
+const REPRO_VERSION: u32 = 3;
+
+/// Budget a search was started with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchBudget {
+    /// Time budget in milliseconds, see [`MctsEngine::run_search`].
+    Millis(u128),
+    /// Fixed number of iterations, see [`MctsEngine::run_iterations`].
+    Iterations(u32),
+}
+
+/// Everything needed to replay a search. See the [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReproBundle {
+    /// Seed the engine was seeded with when the search tree was created.
+    pub seed: u64,
+    /// Parameters of the search. Their [`seed`](MctsConfig::seed) is not recorded, since the tree
+    /// was seeded with `seed`. Custom selection and rollout policies cannot be recorded.
+    pub config: MctsConfig,
+    /// Position at the root of the search tree.
+    pub position: Board,
+    /// Budget of the last search. Only informational, since time budgets cannot be replayed.
+    pub budget: SearchBudget,
+    /// Total number of iterations performed since the search tree was created, possibly by
+    /// several searches.
+    pub iterations: u32,
+    /// Best move after the last search.
+    pub best_move: Option<Move>,
+}
+
+impl ReproBundle {
+    /// Replays the search on a fresh engine. Returns the engine after the search, so that the
+    /// result can be compared with [`best_move`](Self::best_move).
+    pub fn replay(&self) -> MctsEngine {
+        let config = MctsConfig {
+            seed: Some(self.seed),
+            ..self.config.clone()
+        };
+        let mut mcts = MctsEngine::with_config(self.position, config);
+        mcts.run_iterations(self.iterations);
+        mcts
+    }
+}
+
+impl Display for ReproBundle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "uttt-repro {}", REPRO_VERSION)?;
+        writeln!(f, "seed {:016x}", self.seed)?;
+        write!(f, "board")?;
+        for sub_board in &self.position.board {
+            write!(f, " {:03x}:{:03x}", sub_board.x.0, sub_board.o.0)?;
+        }
+        writeln!(f)?;
+        let sub_wins = self.position.sub_wins;
+        writeln!(
+            f,
+            "sub-wins {:03x}:{:03x}:{:03x}",
+            sub_wins.x.0, sub_wins.o.0, sub_wins.tie.0
+        )?;
+        writeln!(f, "player {:?}", self.position.player_to_move)?;
+        writeln!(f, "next-sub-board {}", u8::from(self.position.constraint()))?;
+        if self.position.rules.strict_game_over {
+            writeln!(f, "rules strict-game-over")?;
+        } else {
+            writeln!(f, "rules default")?;
+        }
+        write_config(f, &self.config)?;
+        match self.budget {
+            SearchBudget::Millis(ms) => writeln!(f, "budget millis {}", ms)?,
+            SearchBudget::Iterations(n) => writeln!(f, "budget iterations {}", n)?,
+        }
+        writeln!(f, "iterations {}", self.iterations)?;
+        match self.best_move {
+            Some(m) => writeln!(f, "best-move {},{}", m.major, m.minor),
+            None => writeln!(f, "best-move none"),
+        }
+    }
+}
+
+impl FromStr for ReproBundle {
+    type Err = ReproError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |msg: &str| ReproError::Invalid(msg.to_string());
+        let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+        // Returns the value of the next line, which must start with `key`.
+        let mut field = |key: &str| {
+            lines
+                .next()
+                .and_then(|line| line.strip_prefix(key))
+                .filter(|value| value.starts_with(' '))
+                .map(str::trim)
+                .ok_or_else(|| ReproError::Invalid(format!("expected `{}`", key)))
+        };
+        // Parses a 9 bit word written in hex.
+        let word = |value: &str| {
+            u16::from_str_radix(value, 16)
+                .ok()
+                .filter(|&word| word <= 0b111111111)
+                .ok_or_else(|| invalid("invalid board word"))
+        };
+
+        let version = match field("uttt-repro")?.parse() {
+            Ok(version @ 1..=REPRO_VERSION) => version,
+            _ => return Err(invalid("unsupported version")),
+        };
+        let seed = u64::from_str_radix(field("seed")?, 16).map_err(|_| invalid("invalid seed"))?;
+
+        let mut position = Board::new();
+        let sub_boards: Vec<_> = field("board")?.split_whitespace().collect();
+        if sub_boards.len() != 9 {
+            return Err(invalid("expected 9 sub-boards"));
+        }
+        for (sub_board, value) in position.board.iter_mut().zip(sub_boards) {
+            let (x, o) = value
+                .split_once(':')
+                .ok_or_else(|| invalid("invalid sub-board"))?;
+            *sub_board = SubBoard {
+                x: BitBoard(word(x)?),
+                o: BitBoard(word(o)?),
+            };
+        }
+        let sub_wins: Vec<_> = field("sub-wins")?.split(':').collect();
+        let [x, o, tie] = sub_wins[..] else {
+            return Err(invalid("invalid sub-wins"));
+        };
+        let sub_wins = (word(x)?, word(o)?, word(tie)?);
+        position.player_to_move = match field("player")? {
+            "X" => Player::X,
+            "O" => Player::O,
+            _ => return Err(invalid("invalid player")),
+        };
+        let constraint = field("next-sub-board")?
+            .parse()
+            .ok()
+            .and_then(|next: u8| NextSubBoard::try_from(next).ok())
+            .ok_or_else(|| invalid("invalid next-sub-board"))?;
+        position.set_constraint(constraint);
+        if version >= 2 {
+            position.rules = match field("rules")? {
+                "default" => Rules::default(),
+                "strict-game-over" => Rules {
+                    strict_game_over: true,
+                },
+                _ => return Err(invalid("invalid rules")),
+            };
+        }
+        let config = if version >= 3 {
+            parse_config(&mut field)?
+        } else {
+            MctsConfig {
+                solver: false,
+                ..MctsConfig::default()
+            }
+        };
+        // The move count and the Zobrist hash are not part of the format.
+        position.update_derived_state();
+        let recomputed = position.sub_wins;
+        if (recomputed.x.0, recomputed.o.0, recomputed.tie.0) != sub_wins {
+            return Err(invalid("sub-wins do not match the board"));
+        }
+        position.validate()?;
+
+        let budget = field("budget")?;
+        let budget = match budget.split_once(' ') {
+            Some(("millis", ms)) => ms.parse().ok().map(SearchBudget::Millis),
+            Some(("iterations", n)) => n.parse().ok().map(SearchBudget::Iterations),
+            _ => None,
+        }
+        .ok_or_else(|| invalid("invalid budget"))?;
+        let iterations = field("iterations")?
+            .parse()
+            .map_err(|_| invalid("invalid iterations"))?;
+        let best_move = match field("best-move")? {
+            "none" => None,
+            m => Some(m.parse()?),
+        };
+
+        Ok(Self {
+            seed,
+            config,
+            position,
+            budget,
+            iterations,
+            best_move,
+        })
+    }
+}
+
+/// Writes `value`, or `none`.
+fn write_option(f: &mut Formatter<'_>, key: &str, value: Option<impl Display>) -> fmt::Result {
+    match value {
+        Some(value) => writeln!(f, "{} {}", key, value),
+        None => writeln!(f, "{} none", key),
+    }
+}
+
+/// Writes the lines of the [`MctsConfig`], except for the seed.
+fn write_config(f: &mut Formatter<'_>, config: &MctsConfig) -> fmt::Result {
+    writeln!(f, "exploration {}", config.exploration)?;
+    let selection = match config.selection {
+        SelectionFormula::Ucb1 => "ucb1",
+        SelectionFormula::Ucb1Tuned => "ucb1-tuned",
+        SelectionFormula::Puct => "puct",
+        SelectionFormula::Custom(_) => "custom",
+    };
+    writeln!(f, "selection {}", selection)?;
+    write_option(f, "first-play-urgency", config.first_play_urgency)?;
+    match &config.rollout {
+        Rollout::Random => writeln!(f, "rollout random")?,
+        Rollout::Heuristic(heuristic) => writeln!(f, "rollout heuristic {}", heuristic.epsilon)?,
+        Rollout::Custom(_) => writeln!(f, "rollout custom")?,
+    }
+    write_option(f, "rollout-depth", config.rollout_depth)?;
+    let policy = match config.eval_cache_policy {
+        ReplacementPolicy::Lru => "lru",
+        ReplacementPolicy::Fifo => "fifo",
+    };
+    match config.eval_cache_size {
+        Some(size) => writeln!(f, "eval-cache {} {}", size, policy)?,
+        None => writeln!(f, "eval-cache none {}", policy)?,
+    }
+    write_option(f, "max-nodes", config.max_nodes)?;
+    write_option(f, "max-memory", config.max_memory)?;
+    let node_limit_policy = match config.node_limit_policy {
+        NodeLimitPolicy::StopExpanding => "stop-expanding",
+        NodeLimitPolicy::Prune => "prune",
+    };
+    writeln!(f, "node-limit-policy {}", node_limit_policy)?;
+    write_option(f, "max-depth", config.max_depth)?;
+    writeln!(
+        f,
+        "root-noise {} {}",
+        config.root_noise_epsilon, config.root_noise_alpha
+    )?;
+    writeln!(f, "solver {}", config.solver)?;
+    writeln!(f, "transpositions {}", config.transpositions)?;
+    writeln!(
+        f,
+        "early-stop {} {}",
+        config.early_stop, config.early_stop_visit_share
+    )
+}
+
+/// Parses `value`, reporting `key` if it is invalid.
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, ReproError> {
+    value
+        .parse()
+        .map_err(|_| ReproError::Invalid(format!("invalid {}", key)))
+}
+
+/// Parses `value`, which may be `none`.
+fn parse_option<T: FromStr>(key: &str, value: &str) -> Result<Option<T>, ReproError> {
+    match value {
+        "none" => Ok(None),
+        value => parse(key, value).map(Some),
+    }
+}
+
+/// Splits `value` into two words.
+fn split_pair<'a>(key: &str, value: &'a str) -> Result<(&'a str, &'a str), ReproError> {
+    value
+        .split_once(' ')
+        .ok_or_else(|| ReproError::Invalid(format!("invalid {}", key)))
+}
+
+/// Parses the lines written by [`write_config`], reading the value of each key with `field`.
+fn parse_config<'a>(
+    field: &mut impl FnMut(&str) -> Result<&'a str, ReproError>,
+) -> Result<MctsConfig, ReproError> {
+    let custom = || ReproError::Invalid("custom policies cannot be replayed".to_string());
+    let exploration = parse("exploration", field("exploration")?)?;
+    let selection = match field("selection")? {
+        "ucb1" => SelectionFormula::Ucb1,
+        "ucb1-tuned" => SelectionFormula::Ucb1Tuned,
+        "puct" => SelectionFormula::Puct,
+        "custom" => return Err(custom()),
+        _ => return Err(ReproError::Invalid("invalid selection".to_string())),
+    };
+    let first_play_urgency = parse_option("first-play-urgency", field("first-play-urgency")?)?;
+    let rollout = match field("rollout")? {
+        "random" => Rollout::Random,
+        "custom" => return Err(custom()),
+        value => match value.strip_prefix("heuristic ") {
+            Some(epsilon) => Rollout::Heuristic(HeuristicRollout {
+                epsilon: parse("rollout", epsilon)?,
+            }),
+            None => return Err(ReproError::Invalid("invalid rollout".to_string())),
+        },
+    };
+    let rollout_depth = parse_option("rollout-depth", field("rollout-depth")?)?;
+    let (size, policy) = split_pair("eval-cache", field("eval-cache")?)?;
+    let eval_cache_size = parse_option("eval-cache", size)?;
+    let eval_cache_policy = match policy {
+        "lru" => ReplacementPolicy::Lru,
+        "fifo" => ReplacementPolicy::Fifo,
+        _ => return Err(ReproError::Invalid("invalid eval-cache".to_string())),
+    };
+    let max_nodes = parse_option("max-nodes", field("max-nodes")?)?;
+    let max_memory = parse_option("max-memory", field("max-memory")?)?;
+    let node_limit_policy = match field("node-limit-policy")? {
+        "stop-expanding" => NodeLimitPolicy::StopExpanding,
+        "prune" => NodeLimitPolicy::Prune,
+        _ => return Err(ReproError::Invalid("invalid node-limit-policy".to_string())),
+    };
+    let max_depth = parse_option("max-depth", field("max-depth")?)?;
+    let (epsilon, alpha) = split_pair("root-noise", field("root-noise")?)?;
+    let solver = parse("solver", field("solver")?)?;
+    let transpositions = parse("transpositions", field("transpositions")?)?;
+    let (early_stop, visit_share) = split_pair("early-stop", field("early-stop")?)?;
+
+    Ok(MctsConfig {
+        exploration,
+        selection,
+        first_play_urgency,
+        rollout,
+        rollout_depth,
+        eval_cache_size,
+        eval_cache_policy,
+        max_nodes,
+        max_memory,
+        node_limit_policy,
+        max_depth,
+        root_noise_epsilon: parse("root-noise", epsilon)?,
+        root_noise_alpha: parse("root-noise", alpha)?,
+        solver,
+        transpositions,
+        early_stop: parse("early-stop", early_stop)?,
+        early_stop_visit_share: parse("early-stop", visit_share)?,
+        seed: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReproUnavailable;
+
+    /// Position after a few moves, where the move count and the Zobrist hash are not those of the
+    /// empty board.
+    fn mid_game() -> Board {
+        let mut board = Board::new();
+        for (major, minor) in [(4, 4), (4, 0), (0, 8), (8, 4), (4, 8), (8, 0)] {
+            board = board.advance_state(Move { major, minor }).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn round_trip_mid_game() {
+        let mut mcts = MctsEngine::with_seed(mid_game(), 42);
+        mcts.set_record_repro(true);
+        mcts.run_iterations(500);
+        let bundle = mcts.repro().unwrap().clone();
+
+        let parsed: ReproBundle = bundle.to_string().parse().unwrap();
+        assert_eq!(parsed, bundle);
+        assert_eq!(parsed.position.move_count(), 6);
+        assert_eq!(parsed.replay().best_move().ok(), bundle.best_move);
+    }
+
+    #[test]
+    fn round_trip_config() {
+        let config = MctsConfig {
+            exploration: 0.9,
+            selection: SelectionFormula::Puct,
+            first_play_urgency: Some(0.5),
+            rollout: Rollout::Heuristic(HeuristicRollout { epsilon: 0.25 }),
+            rollout_depth: Some(6),
+            eval_cache_size: Some(1 << 10),
+            eval_cache_policy: ReplacementPolicy::Fifo,
+            max_nodes: Some(10_000),
+            node_limit_policy: NodeLimitPolicy::Prune,
+            max_depth: Some(20),
+            root_noise_epsilon: 0.25,
+            root_noise_alpha: 0.3,
+            solver: false,
+            transpositions: true,
+            early_stop: true,
+            early_stop_visit_share: 0.75,
+            seed: Some(7),
+            ..MctsConfig::default()
+        };
+        let mut mcts = MctsEngine::with_config(mid_game(), config.clone());
+        mcts.set_record_repro(true);
+        mcts.run_iterations(500);
+        let bundle = mcts.repro().unwrap().clone();
+        assert_eq!(
+            bundle.config,
+            MctsConfig {
+                seed: None,
+                ..config
+            }
+        );
+
+        let parsed: ReproBundle = bundle.to_string().parse().unwrap();
+        assert_eq!(parsed, bundle);
+        assert_eq!(parsed.replay().best_move().ok(), bundle.best_move);
+    }
+
+    #[test]
+    fn unavailable_repro() {
+        let mut mcts = MctsEngine::with_seed(mid_game(), 42);
+        mcts.run_iterations(10);
+        assert_eq!(mcts.repro().unwrap_err(), ReproUnavailable::Disabled);
+
+        mcts.set_record_repro(true);
+        mcts.set_position(mid_game());
+        assert_eq!(mcts.repro().unwrap_err(), ReproUnavailable::NoSearch);
+
+        mcts.run_iterations(100);
+        let m = mcts.best_move().unwrap();
+        mcts.advance_root(m).unwrap();
+        mcts.run_iterations(10);
+        assert_eq!(mcts.repro().unwrap_err(), ReproUnavailable::ReusedTree);
+    }
+
+    #[test]
+    fn replay_version_1() {
+        let config = MctsConfig {
+            solver: false,
+            seed: Some(42),
+            ..MctsConfig::default()
+        };
+        let mut mcts = MctsEngine::with_config(mid_game(), config);
+        mcts.set_record_repro(true);
+        mcts.run_iterations(500);
+        let bundle = mcts.repro().unwrap().clone();
+
+        // Version 1 has neither the rules nor the config lines.
+        let config_keys = [
+            "rules",
+            "exploration",
+            "selection",
+            "first-play-urgency",
+            "rollout",
+            "eval-cache",
+            "max-",
+            "node-limit-policy",
+            "root-noise",
+            "solver",
+            "transpositions",
+            "early-stop",
+        ];
+        let version_1 = bundle
+            .to_string()
+            .lines()
+            .filter(|line| !config_keys.iter().any(|key| line.starts_with(key)))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .replacen("uttt-repro 3", "uttt-repro 1", 1);
+        let parsed: ReproBundle = version_1.parse().unwrap();
+        assert_eq!(parsed, bundle);
+        assert_eq!(parsed.replay().best_move().ok(), bundle.best_move);
+    }
+
+    #[test]
+    fn reject_custom_policy() {
+        let mut mcts = MctsEngine::with_seed(mid_game(), 42);
+        mcts.set_record_repro(true);
+        mcts.run_iterations(10);
+        let bundle = mcts.repro().unwrap().to_string();
+
+        let custom = bundle.replacen("\nrollout random\n", "\nrollout custom\n", 1);
+        assert_ne!(custom, bundle);
+        assert!(matches!(
+            custom.parse::<ReproBundle>(),
+            Err(ReproError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,85 @@
+//! Move selection in the rollouts of the search.
+
+use std::fmt;
+
+use rand::prelude::SliceRandom;
+use rand::{Rng, RngCore};
+
+use uttt_core::{Board, Move};
+
+/// Picks the moves of the simulated games played by the rollouts of an
+/// [`MctsEngine`](crate::MctsEngine). Implement this to plug a custom policy in with
+/// [`Rollout::Custom`](crate::Rollout::Custom).
+///
+/// The policy must be `Send + Sync` because the engine holding it can be moved to another thread,
+/// e.g. by [`MctsEngine::run_search_streaming`](crate::MctsEngine::run_search_streaming). All
+/// randomness should come from `rng`, so that seeded searches stay deterministic.
+pub trait RolloutPolicy: fmt::Debug + Send + Sync {
+    /// Returns the move to play in `board`, which must be one of `moves`. `moves` are the legal
+    /// moves of `board` and never empty.
+    fn pick(&self, board: &Board, moves: &[Move], rng: &mut dyn RngCore) -> Move;
+}
+
+/// Classifies a legal move of `board` for the player to move: `3` if it wins the game, `2` if it
+/// wins a sub-board, `1` if it blocks the opponent from winning a sub-board and `0` otherwise.
+pub(crate) fn tactical_priority(board: &Board, m: Move) -> u32 {
+    let player = board.player_to_move;
+    let sub_board = board.board[m.major as usize];
+    let occupied = sub_board.x | sub_board.o;
+    let bit = 1 << m.minor;
+    if sub_board[player].completing_moves(occupied).0 & bit != 0 {
+        let wins = board.sub_wins;
+        let decided = wins.x | wins.o | wins.tie;
+        if wins[player].completing_moves(decided).0 & (1 << m.major) != 0 {
+            3
+        } else {
+            2
+        }
+    } else if sub_board[player.opponent()].completing_moves(occupied).0 & bit != 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Rollout policy that plays tactically sensible moves instead of uniformly random ones.
+///
+/// In order of priority, it plays a move that wins the game, a move that wins a sub-board or a
+/// move that blocks the opponent from winning a sub-board. Ties are broken at random, and if no
+/// move is any of those, it picks a uniformly random move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeuristicRollout {
+    /// Probability of ignoring the heuristics and playing a uniformly random move. Some randomness
+    /// keeps the rollouts from all playing out the same way. Defaults to `0.1`.
+    pub epsilon: f32,
+}
+
+impl Default for HeuristicRollout {
+    fn default() -> Self {
+        Self { epsilon: 0.1 }
+    }
+}
+
+impl RolloutPolicy for HeuristicRollout {
+    fn pick(&self, board: &Board, moves: &[Move], rng: &mut dyn RngCore) -> Move {
+        if rng.gen::<f32>() < self.epsilon {
+            return *moves.choose(rng).unwrap();
+        }
+        // Candidates for the best priority found so far.
+        let mut candidates = [Move::new(0, 0); 81];
+        let mut len = 0;
+        let mut best = 0;
+        for &m in moves {
+            let priority = tactical_priority(board, m);
+            if priority > best {
+                best = priority;
+                len = 0;
+            }
+            if priority == best {
+                candidates[len] = m;
+                len += 1;
+            }
+        }
+        *candidates[..len].choose(rng).unwrap()
+    }
+}
@@ -0,0 +1,56 @@
+//! Interface shared by the engines, so that tournaments, the CLI and protocol servers can use any
+//! of them.
+
+use std::time::Duration;
+
+use uttt_core::{Board, Move};
+
+use crate::SearchHandle;
+
+/// Limits of a search started with [`Engine::go`]. The search stops at the first limit reached;
+/// limits that are not set do not apply. Engines ignore the limits that do not make sense for
+/// them.
+///
+/// A search without any limit, such as [`SearchLimits::default`], runs until it is stopped
+/// through the [`SearchHandle`] of the engine, which has to be taken before calling
+/// [`Engine::go`] since `go` borrows the engine for the whole search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchLimits {
+    /// Time budget in milliseconds.
+    pub time_ms: Option<u128>,
+    /// Maximum number of nodes searched. For an [`MctsEngine`](crate::MctsEngine), this is the
+    /// number of iterations.
+    pub nodes: Option<u64>,
+    /// Maximum search depth, for engines that search to a fixed depth.
+    pub depth: Option<u32>,
+}
+
+/// Result of a search started with [`Engine::go`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    /// Best move found, or `None` if the game is over.
+    pub best_move: Option<Move>,
+    /// Estimated probability that the player to move wins with `best_move`, counting ties as half
+    /// a win.
+    pub win_rate: Option<f32>,
+    /// Principal variation, starting with `best_move`.
+    pub pv: Vec<Move>,
+    /// Number of nodes searched.
+    pub nodes: u64,
+    /// Depth of the last complete iteration, for engines that search to a fixed depth.
+    pub depth: Option<u32>,
+    pub elapsed: Duration,
+}
+
+/// A game-playing engine.
+pub trait Engine {
+    /// Discards what the engine knows about the previous position and starts searching `board`.
+    fn set_position(&mut self, board: Board);
+
+    /// Searches the current position within `limits` and returns the best move found.
+    fn go(&mut self, limits: SearchLimits) -> SearchResult;
+
+    /// Returns a handle that stops the searches of the engine from another thread. If no search is
+    /// running, the next search stops right after it starts.
+    fn search_handle(&self) -> SearchHandle;
+}
@@ -0,0 +1,103 @@
+//! Formulas picking the child to descend into during the selection phase of the search.
+
+use std::fmt;
+
+use uttt_core::{Board, Move, MovesIter};
+
+use crate::rollout::tactical_priority;
+
+/// Statistics of a node of the search tree, as seen by a [`SelectionPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelectionStats {
+    /// Number of rollouts through the node.
+    pub visits: u32,
+    /// Sum of the scores of the rollouts through the node, for the player who made the move
+    /// leading to it. A win scores `1.0`, a tie `0.5` and a loss `0.0`.
+    pub wins: f32,
+    /// Sum of the squares of the scores in `wins`, for estimating their variance.
+    pub squared_wins: f32,
+    /// Prior probability of the move leading to the node, see [`SelectionPolicy::prior`].
+    pub prior: f32,
+}
+
+impl SelectionStats {
+    /// Returns the average score of the rollouts through the node.
+    pub fn win_rate(&self) -> f32 {
+        self.wins / self.visits as f32
+    }
+}
+
+/// Scores the children of a node during selection; the child with the highest score is descended
+/// into. Implement this to plug a custom formula in with
+/// [`SelectionFormula::Custom`](crate::SelectionFormula::Custom).
+pub trait SelectionPolicy: fmt::Debug + Send + Sync {
+    /// Returns the score of `child`, a child of `parent` that was visited at least once.
+    /// `exploration` is [`MctsConfig::exploration`](crate::MctsConfig::exploration).
+    fn score(&self, parent: &SelectionStats, child: &SelectionStats, exploration: f32) -> f32;
+
+    /// Returns the prior probability of `m` being the best move in `board`, which is stored in
+    /// [`SelectionStats::prior`] when the move is expanded. Defaults to `1.0` for every move, for
+    /// formulas that do not use priors.
+    fn prior(&self, board: &Board, m: Move) -> f32 {
+        let _ = (board, m);
+        1.0
+    }
+}
+
+/// UCB1: the win rate of the child plus `exploration * sqrt(ln(N) / n)`, where `N` and `n` are the
+/// visits of the parent and the child.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ucb1;
+
+impl SelectionPolicy for Ucb1 {
+    #[inline(always)]
+    fn score(&self, parent: &SelectionStats, child: &SelectionStats, exploration: f32) -> f32 {
+        child.win_rate()
+            + exploration * f32::sqrt(f32::ln(parent.visits as f32) / child.visits as f32)
+    }
+}
+
+/// UCB1-Tuned: like [`Ucb1`], but the exploration term of a child shrinks with the observed
+/// variance of its rollout scores, so that moves with consistent results are explored less.
+/// `exploration` is scaled so that the default of `√2` gives the textbook formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ucb1Tuned;
+
+impl SelectionPolicy for Ucb1Tuned {
+    #[inline(always)]
+    fn score(&self, parent: &SelectionStats, child: &SelectionStats, exploration: f32) -> f32 {
+        let n = child.visits as f32;
+        let mean = child.win_rate();
+        let log_ratio = f32::ln(parent.visits as f32) / n;
+        let variance = child.squared_wins / n - mean * mean + f32::sqrt(2.0 * log_ratio);
+        mean + exploration
+            * std::f32::consts::FRAC_1_SQRT_2
+            * f32::sqrt(log_ratio * variance.min(0.25))
+    }
+}
+
+/// PUCT, as in AlphaZero: the win rate of the child plus
+/// `exploration * prior * sqrt(N) / (1 + n)`, where `N` and `n` are the visits of the parent and
+/// the child.
+///
+/// The priors favor moves that win the game, then moves that win a sub-board, then moves that
+/// block the opponent from winning a sub-board, each twice as much as the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Puct;
+
+impl SelectionPolicy for Puct {
+    #[inline(always)]
+    fn score(&self, parent: &SelectionStats, child: &SelectionStats, exploration: f32) -> f32 {
+        child.win_rate()
+            + exploration * child.prior * f32::sqrt(parent.visits as f32)
+                / (1 + child.visits) as f32
+    }
+
+    fn prior(&self, board: &Board, m: Move) -> f32 {
+        let weight = |m| (1 << tactical_priority(board, m)) as f32;
+        let total: f32 = MovesIter::from_mask(board.legal_move_mask())
+            .map(weight)
+            .sum();
+        weight(m) / total
+    }
+}
@@ -1,7 +0,0 @@
-//! Ultimate TicTacToe AI engine written in Rust.
-
-mod state;
-mod engine;
-
-pub use state::*;
-pub use engine::*;
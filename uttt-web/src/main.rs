@@ -1,6 +1,22 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use gloo_timers::future::TimeoutFuture;
+use instant::Instant;
 use sycamore::{futures::spawn_local_scoped, prelude::*};
-use uttt_rs::{Board, MctsEngine, Move, Player, Winner};
+use uttt_core::{Board, Game, GameMove, Move, Outcome, Player, Rules};
+use uttt_engine::{
+    explain_move, Calibration, MctsConfig, MctsEngine, NodeLimitPolicy, BEGINNER_MISTAKE_RATE,
+};
+
+/// Strength of the "Noob" difficulty, which also makes the AI play occasional mistakes.
+const NOOB_STRENGTH: u128 = 50;
+/// Iterations searched between two updates of the page, so that it stays responsive while the AI
+/// is thinking.
+const ITERATIONS_PER_SLICE: u32 = 10_000;
+/// Memory the search tree may take up. The tree is reused from move to move, so on the hardest
+/// difficulties it would otherwise keep growing until the page runs out of memory.
+const MAX_TREE_MEMORY: usize = 128 << 20;
 
 #[component]
 fn App() -> View {
@@ -20,85 +36,123 @@ fn use_board_cell(
     let major_i = major.0 * 3 + major.1;
     let minor_i = minor.0 * 3 + minor.1;
 
-    create_selector(move || {
-        let sub_board = board.get().board[major_i as usize];
-        let mask = 1 << minor_i;
-        if sub_board.x.0 & mask != 0 {
-            Some(Player::X)
-        } else if sub_board.o.0 & mask != 0 {
-            Some(Player::O)
-        } else {
-            None
-        }
-    })
+    create_selector(move || board.get().cell(major_i, minor_i))
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum SubBoardState {
-    Winner(Winner),
+    Decided(Outcome),
     Next,
+    InProgress,
 }
 
 fn use_sub_board_state(board: ReadSignal<Board>, major: (u32, u32)) -> ReadSignal<SubBoardState> {
     let i = major.0 * 3 + major.1;
 
-    create_selector(move || {
-        // Check win state of sub-board.
-        let sub_board = board.get().sub_wins;
-        let mask = 1 << i;
-        if sub_board.x.0 & mask != 0 {
-            SubBoardState::Winner(Winner::X)
-        } else if sub_board.o.0 & mask != 0 {
-            SubBoardState::Winner(Winner::O)
-        } else if sub_board.tie.0 & mask != 0 {
-            SubBoardState::Winner(Winner::Tie)
-        } else if board.get().next_sub_board == 9 || board.get().next_sub_board == i {
-            SubBoardState::Next
-        } else {
-            SubBoardState::Winner(Winner::InProgress)
-        }
+    create_selector(move || match board.get().sub_board_winner(i) {
+        Some(outcome) => SubBoardState::Decided(outcome),
+        None if board.get().playable_sub_boards() & 1 << i != 0 => SubBoardState::Next,
+        None => SubBoardState::InProgress,
     })
 }
 
 #[component]
 fn GameView() -> View {
-    let board = create_signal(Board::new());
+    // Moves are rejected once the game is decided.
+    let board = create_signal(Board::with_rules(Rules {
+        strict_game_over: true,
+    }));
 
+    // Difficulty is a strength, i.e. the think time on the reference machine. The engine runs a
+    // fixed number of iterations so that it plays equally well on slow and fast devices.
     let difficulty = create_signal(100);
+    provide_context(MctsEngine::calibrate());
 
     let msg = create_signal("".to_string());
-    let move_list = create_signal(Vec::<(Player, Move, Board)>::new());
+    let game = create_signal(Game::new(board.get_untracked()));
+    // Engine of the last AI move, whose tree is reused for the next one.
+    let engine = Rc::new(RefCell::new(None::<MctsEngine>));
 
     // When board changes and player is O, run AI.
     create_effect(move || {
         if board.get().player_to_move == Player::O {
             // Make sure that game is not finished.
-            if board.get().winner() != Winner::InProgress {
+            if board.get().winner().is_some() {
                 return;
             }
             msg.set("Running AI...".to_string());
+            let engine = engine.clone();
             // We run the AI in the next micro-task to allow for transitions to finish.
             spawn_local_scoped(async move {
                 // Wait 300ms because that is the duration for the transition for sub-board state.
                 TimeoutFuture::new(300).await;
-                let mcts = MctsEngine::new();
-                mcts.initialize(board.get());
-                let (iters, moves) = mcts.run_search(difficulty.get_untracked());
-                let m = mcts.best_move();
+                let start = Instant::now();
+                // Continue the previous search from the player's move if there is one.
+                let position = board.get();
+                let previous = engine.borrow_mut().take();
+                let mut mcts = previous
+                    .and_then(|mut mcts| {
+                        mcts.advance_root(position.last_move()?).ok()?;
+                        (mcts.position() == position).then_some(mcts)
+                    })
+                    .unwrap_or_else(|| {
+                        let config = MctsConfig {
+                            max_memory: Some(MAX_TREE_MEMORY),
+                            node_limit_policy: NodeLimitPolicy::Prune,
+                            ..MctsConfig::default()
+                        };
+                        MctsEngine::with_config(position, config)
+                    });
+                let iterations = Calibration::iterations_for_strength(difficulty.get_untracked());
+                let (mut iters, mut moves) = (0, 0);
+                let mut remaining = iterations;
+                while remaining > 0 {
+                    let slice = remaining.min(ITERATIONS_PER_SLICE);
+                    let (slice_iters, slice_moves) = mcts.run_iterations(slice);
+                    iters += slice_iters;
+                    moves += slice_moves;
+                    remaining -= slice;
+                    if remaining > 0 {
+                        msg.set(format!(
+                            "Running AI... {}%",
+                            (iterations - remaining) as u64 * 100 / iterations as u64
+                        ));
+                        // Yield to the browser so that the message is rendered.
+                        TimeoutFuture::new(0).await;
+                    }
+                }
+                // The game is in progress so there is always a legal move.
+                let eval = mcts.best_move_with_stats().expect("game is in progress");
+                let m = if difficulty.get_untracked() <= NOOB_STRENGTH {
+                    mcts.beginner_move(BEGINNER_MISTAKE_RATE)
+                        .expect("game is in progress")
+                } else {
+                    eval.mv
+                };
+                let reasons = explain_move(&board.get(), m);
                 board.set(board.get().advance_state(m).unwrap());
-                msg.set(format!(
+                let mut text = format!(
                     "AI simulated {} games and {} moves in {}ms.",
                     iters,
                     moves,
-                    difficulty.get_untracked()
-                ));
-                move_list.update(|list| list.push((Player::O, m, board.get())));
+                    start.elapsed().as_millis()
+                );
+                if !reasons.is_empty() {
+                    let reasons: Vec<_> = reasons.iter().map(ToString::to_string).collect();
+                    text += &format!(" The move {}.", reasons.join(", "));
+                }
+                text += &format!(" It thinks it is {:.0}% to win.", eval.win_rate * 100.0);
+                msg.set(text);
+                game.update(|game| game.play(m).expect("move is legal"));
+                mcts.advance_root(m).expect("move is legal");
+                *engine.borrow_mut() = Some(mcts);
             });
         }
     });
 
-    provide_context(move_list);
+    provide_context(game);
     provide_context(board);
+    provide_context(msg);
     view! {
         DifficultySelector(difficulty=difficulty)
         p(class="h-12 py-2") {
@@ -133,11 +187,11 @@ fn SubBoard(major: (u32, u32)) -> View {
     let board = use_context::<Signal<Board>>();
     let state = use_sub_board_state(*board, major);
     let class = create_memo(move || match state.get() {
-        SubBoardState::Winner(Winner::X) => "sub-board x",
-        SubBoardState::Winner(Winner::O) => "sub-board o",
-        SubBoardState::Winner(Winner::Tie) => "sub-board tie",
+        SubBoardState::Decided(Outcome::X) => "sub-board x",
+        SubBoardState::Decided(Outcome::O) => "sub-board o",
+        SubBoardState::Decided(Outcome::Tie) => "sub-board tie",
         SubBoardState::Next => "sub-board next",
-        SubBoardState::Winner(Winner::InProgress) => "sub-board in-progress",
+        SubBoardState::InProgress => "sub-board in-progress",
     });
 
     view! {
@@ -157,7 +211,8 @@ fn SubBoard(major: (u32, u32)) -> View {
 
 #[component(inline_props)]
 fn BoardCell(board: Signal<Board>, major: (u32, u32), minor: (u32, u32)) -> View {
-    let move_list = use_context::<Signal<Vec<(Player, Move, Board)>>>();
+    let game = use_context::<Signal<Game>>();
+    let msg = use_context::<Signal<String>>();
 
     let state = use_board_cell(*board, major, minor);
     let class = create_memo(move || match state.get() {
@@ -171,17 +226,14 @@ fn BoardCell(board: Signal<Board>, major: (u32, u32), minor: (u32, u32)) -> View
         if board.get().player_to_move != Player::X {
             return;
         }
-        // Make sure that game is not finished.
-        if board.get().winner() != Winner::InProgress {
-            return;
-        }
         // Update board.
         let m = Move::new(major.0 * 3 + major.1, minor.0 * 3 + minor.1);
-        let next = board.get().advance_state(m);
-        if let Some(next) = next {
-            // Make sure that move is valid. If invalid, do nothing.
-            board.set(next);
-            move_list.update(|list| list.push((Player::X, m, next)));
+        match board.get().try_advance(m) {
+            Ok(next) => {
+                board.set(next);
+                game.update(|game| game.play(m).expect("move is legal"));
+            }
+            Err(err) => msg.set(format!("Illegal move: {}.", err)),
         }
     };
 
@@ -204,7 +256,7 @@ fn DifficultySelector(difficulty: Signal<u128>) -> View {
         div(class="flex flex-row space-x-4") {
             Indexed(
                 list=create_signal( vec![
-                    ("Noob", 50),
+                    ("Noob", NOOB_STRENGTH),
                     ("Easy", 100),
                     ("Medium", 500),
                     ("Hard", 1000),
@@ -222,6 +274,9 @@ fn DifficultySelector(difficulty: Signal<u128>) -> View {
 #[component(inline_props)]
 fn DifficultyOption(name: &'static str, value: u128) -> View {
     let difficulty = use_context::<Signal<u128>>();
+    let calibration = use_context::<Calibration>();
+    // Estimated think time on this device.
+    let estimate = calibration.millis_for_iterations(Calibration::iterations_for_strength(value));
     let class = create_memo(move || {
         if difficulty.get() == value {
             "font-bold underline"
@@ -230,13 +285,14 @@ fn DifficultyOption(name: &'static str, value: u128) -> View {
         }
     });
     view! {
-        button(class=class.get(), on:click=move |_| difficulty.set(value)) { (name) ": " (value) "ms" }
+        button(class=class.get(), on:click=move |_| difficulty.set(value)) { (name) ": ~" (estimate) "ms" }
     }
 }
 
 #[component]
 fn MoveHistory() -> View {
-    let move_list = use_context::<Signal<Vec<(Player, Move, Board)>>>();
+    let game = use_context::<Signal<Game>>();
+    let move_list = create_memo(move || game.with(|game| game.moves().to_vec()));
 
     view! {
         div(class="move-history") {
@@ -251,7 +307,7 @@ fn MoveHistory() -> View {
                 tbody {
                     Indexed(
                         list=move_list,
-                        view=|(player, m, _)| view! {
+                        view=|GameMove { player, m, .. }| view! {
                             tr {
                                 td { (format!("{:?}", player)) }
                                 // Extract row and column from index